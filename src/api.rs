@@ -1,6 +1,7 @@
 use curl::easy::{Easy, List};
+use semver::Version;
 
-use crate::cargo::CargoDependency;
+use crate::dependency::DateField;
 
 pub struct CratesIoResponse {
     pub repository: Option<String>,
@@ -8,6 +9,17 @@ pub struct CratesIoResponse {
     pub latest_version: String,
     pub latest_version_date: Option<String>,
     pub current_version_date: Option<String>,
+    pub downloads: Option<u64>,
+    pub recent_downloads: Option<u64>,
+    /// Stable versions released between the current and the latest one, oldest first
+    pub intermediate_versions: Vec<String>,
+    /// Stable versions released between the current and the latest one that were yanked, oldest
+    /// first
+    pub yanked_versions: Vec<String>,
+    /// `rust-version` declared by the latest version, if any
+    pub latest_rust_version: Option<String>,
+    /// Whether the current version was yanked
+    pub current_version_yanked: bool,
 }
 
 fn get_string_from_value(
@@ -25,6 +37,13 @@ fn get_string_from_value(
     )
 }
 
+fn get_u64_from_value(
+    value: Option<&serde_json::Map<String, serde_json::Value>>,
+    key: &str,
+) -> Option<u64> {
+    value?.get(key)?.as_u64()
+}
+
 fn get_field_from_versions(
     versions: Option<&Vec<serde_json::Value>>,
     version: &str,
@@ -41,62 +60,365 @@ fn get_field_from_versions(
     )
 }
 
+/// Parses a `rust-version`, which may omit the patch component (e.g. `1.70`)
+pub fn parse_rust_version(rust_version: &str) -> Option<Version> {
+    let rust_version = rust_version.trim();
+    Version::parse(rust_version)
+        .or_else(|_| Version::parse(&format!("{rust_version}.0")))
+        .ok()
+}
+
+/// Newest stable, non-yanked version whose `rust_version` is compatible with the given one
+fn get_latest_compatible_version(
+    versions: Option<&Vec<serde_json::Value>>,
+    rust_version: &Version,
+) -> Option<String> {
+    versions?
+        .iter()
+        .filter(|v| !is_yanked(v))
+        .filter(|v| {
+            v.get("rust_version")
+                .and_then(|r| r.as_str())
+                .and_then(parse_rust_version)
+                .is_none_or(|r| &r <= rust_version)
+        })
+        .flat_map(|v| Version::parse(v.get("num")?.as_str()?).ok())
+        .filter(|v| v.pre.is_empty())
+        .max()
+        .map(|v| v.to_string())
+}
+
+fn is_version_yanked(versions: Option<&Vec<serde_json::Value>>, version: &str) -> bool {
+    versions.is_some_and(|versions| {
+        versions
+            .iter()
+            .any(|v| v.get("num").and_then(|n| n.as_str()) == Some(version) && is_yanked(v))
+    })
+}
+
+fn is_yanked(version: &serde_json::Value) -> bool {
+    version
+        .get("yanked")
+        .and_then(|y| y.as_bool())
+        .unwrap_or(false)
+}
+
+/// Stable, non-yanked versions strictly between the current and the latest one, oldest first
+fn get_intermediate_versions(
+    versions: Option<&Vec<serde_json::Value>>,
+    current_version: &str,
+    latest_version: &str,
+) -> Vec<String> {
+    get_versions_between(versions, current_version, latest_version, false)
+}
+
+/// Stable, yanked versions strictly between the current and the latest one, oldest first
+fn get_yanked_versions(
+    versions: Option<&Vec<serde_json::Value>>,
+    current_version: &str,
+    latest_version: &str,
+) -> Vec<String> {
+    get_versions_between(versions, current_version, latest_version, true)
+}
+
+fn get_versions_between(
+    versions: Option<&Vec<serde_json::Value>>,
+    current_version: &str,
+    latest_version: &str,
+    yanked: bool,
+) -> Vec<String> {
+    let (Ok(current_version), Ok(latest_version), Some(versions)) = (
+        Version::parse(current_version),
+        Version::parse(latest_version),
+        versions,
+    ) else {
+        return vec![];
+    };
+
+    let mut intermediate_versions = versions
+        .iter()
+        .filter(|v| is_yanked(v) == yanked)
+        .flat_map(|v| Version::parse(v.get("num")?.as_str()?).ok())
+        .filter(|v| v.pre.is_empty() && *v > current_version && *v < latest_version)
+        .collect::<Vec<_>>();
+    intermediate_versions.sort();
+
+    intermediate_versions
+        .into_iter()
+        .map(|v| v.to_string())
+        .collect()
+}
+
 impl CratesIoResponse {
-    fn from_value(value: serde_json::Value, version: &str) -> Self {
+    /// Fails when the response has no crate to take the latest version from, e.g. an empty body,
+    /// or when the latest version is not a valid semver version, so that a malformed response is
+    /// reported for its crate only instead of as up to date
+    fn from_value(
+        value: serde_json::Value,
+        version: &str,
+        rust_version: Option<&Version>,
+        date_field: DateField,
+    ) -> Result<Self, String> {
         let data = value.get("crate").and_then(|c| c.as_object());
         let versions = value.get("versions").and_then(|c| c.as_array());
+        if data.is_none() && rust_version.is_none() {
+            return Err("no crate in the crates.io response".to_string());
+        }
 
-        let latest_version = get_string_from_value(data, "max_stable_version")
-            .unwrap_or_else(|| version.to_string());
+        let latest_version = match rust_version {
+            Some(rust_version) => get_latest_compatible_version(versions, rust_version),
+            None => get_string_from_value(data, "max_stable_version"),
+        }
+        .unwrap_or_else(|| version.to_string());
+        if let Err(e) = Version::parse(&latest_version) {
+            return Err(format!("invalid latest version \"{latest_version}\": {e}"));
+        }
 
-        Self {
+        Ok(Self {
             repository: get_string_from_value(data, "repository"),
             description: get_string_from_value(data, "description"),
-            latest_version_date: get_field_from_versions(versions, &latest_version, "updated_at"),
-            current_version_date: get_field_from_versions(versions, version, "updated_at"),
+            downloads: get_u64_from_value(data, "downloads"),
+            recent_downloads: get_u64_from_value(data, "recent_downloads"),
+            latest_version_date: get_field_from_versions(
+                versions,
+                &latest_version,
+                date_field.key(),
+            ),
+            current_version_date: get_field_from_versions(versions, version, date_field.key()),
+            intermediate_versions: get_intermediate_versions(versions, version, &latest_version),
+            yanked_versions: get_yanked_versions(versions, version, &latest_version),
+            latest_rust_version: get_field_from_versions(versions, &latest_version, "rust_version"),
+            current_version_yanked: is_version_yanked(versions, version),
             latest_version,
-        }
+        })
     }
 }
 
-pub fn get_latest_version(
-    CargoDependency { name, version, .. }: &CargoDependency,
+/// Details of the errors returned by crates.io instead of a crate, e.g. when it does not exist
+fn errors_from_value(value: &serde_json::Value) -> Option<String> {
+    let errors = value
+        .get("errors")?
+        .as_array()?
+        .iter()
+        .map(|error| {
+            error
+                .get("detail")
+                .and_then(|d| d.as_str())
+                .unwrap_or("unknown error")
+                .trim()
+                .to_string()
+        })
+        .collect::<Vec<_>>();
+
+    Some(if errors.is_empty() {
+        "unknown error".to_string()
+    } else {
+        errors.join(", ")
+    })
+}
+
+/// Parses the body of a crates.io response, failing with the returned errors if any
+fn parse_crates_io_body(
+    body: &[u8],
+    version: &str,
+    rust_version: Option<&Version>,
+    date_field: DateField,
 ) -> Result<CratesIoResponse, Box<dyn std::error::Error>> {
-    let mut headers = List::new();
+    let value = parse_body(body)?;
 
+    if let Some(errors) = errors_from_value(&value) {
+        return Err(errors.into());
+    }
+
+    Ok(CratesIoResponse::from_value(
+        value,
+        version,
+        rust_version,
+        date_field,
+    )?)
+}
+
+fn get_crates_io_url(name: &str) -> String {
+    format!("https://crates.io/api/v1/crates/{name}")
+}
+
+// As required by the crates.io API - https://doc.rust-lang.org/cargo/reference/registry-web-api.html
+pub fn get_default_user_agent() -> String {
     let package_name = env!("CARGO_PKG_NAME");
     let package_repository = env!("CARGO_PKG_REPOSITORY");
 
-    // As required by the crates.io API - https://doc.rust-lang.org/cargo/reference/registry-web-api.html
-    headers.append(&format!(
-        "User-Agent: {package_name} ({package_repository})"
-    ))?;
+    format!("{package_name} ({package_repository})")
+}
 
-    let mut body = vec![];
-    let mut handle = Easy::new();
+fn get_headers(user_agent: &str, content_type: Option<&str>) -> Result<List, curl::Error> {
+    let mut headers = List::new();
+    headers.append(&format!("User-Agent: {user_agent}"))?;
+    if let Some(content_type) = content_type {
+        headers.append(&format!("Content-Type: {content_type}"))?;
+    }
+    Ok(headers)
+}
+
+const OSV_QUERY_URL: &str = "https://api.osv.dev/v1/query";
+
+fn get_osv_query(name: &str, version: &str) -> String {
+    serde_json::json!({
+        "package": { "name": name, "ecosystem": "crates.io" },
+        "version": version,
+    })
+    .to_string()
+}
 
-    handle.get(true)?;
-    handle.url(&format!("https://crates.io/api/v1/crates/{name}"))?;
-    handle.http_headers(headers)?;
+fn parse_body(body: &[u8]) -> Result<serde_json::Value, serde_json::Error> {
+    if body.is_empty() {
+        Ok(serde_json::json!({}))
+    } else {
+        serde_json::from_slice(body)
+    }
+}
+
+/// Where the raw JSON bodies of the crates and of their advisories come from: crates.io and OSV,
+/// or canned responses in tests
+pub trait Registry: Send + Sync {
+    /// Body of the crates.io response for the crate, with all its versions
+    fn fetch_crate(&self, name: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>>;
+
+    /// Body of the OSV response for the version of the crate
+    fn fetch_advisories(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>>;
+}
+
+/// crates.io and OSV, reached with blocking requests
+pub struct CratesIo {
+    pub user_agent: String,
+}
+
+impl Registry for CratesIo {
+    fn fetch_crate(&self, name: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut handle = Easy::new();
+        handle.get(true)?;
+        handle.url(&get_crates_io_url(name))?;
+        handle.http_headers(get_headers(&self.user_agent, None)?)?;
+
+        Ok(perform(&mut handle)?)
+    }
+
+    fn fetch_advisories(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut handle = Easy::new();
+        handle.post(true)?;
+        handle.url(OSV_QUERY_URL)?;
+        handle.http_headers(get_headers(&self.user_agent, Some("application/json"))?)?;
+        handle.post_fields_copy(get_osv_query(name, version).as_bytes())?;
+
+        Ok(perform(&mut handle)?)
+    }
+}
+
+/// Performs the request, returning the body of the response
+fn perform(handle: &mut Easy) -> Result<Vec<u8>, curl::Error> {
+    let mut body = vec![];
 
     {
         let mut transfer = handle.transfer();
-        transfer
-            .write_function(|data| {
-                body.extend_from_slice(data);
-                Ok(data.len())
-            })
-            .unwrap();
-        transfer.perform().unwrap();
+        transfer.write_function(|data| {
+            body.extend_from_slice(data);
+            Ok(data.len())
+        })?;
+        transfer.perform()?;
     }
 
-    let response = if body.is_empty() {
-        "{}".parse()?
-    } else {
-        serde_json::from_slice(&body)?
+    Ok(body)
+}
+
+pub fn get_latest_version(
+    registry: &dyn Registry,
+    name: &str,
+    version: &str,
+    rust_version: Option<&Version>,
+    date_field: DateField,
+) -> Result<CratesIoResponse, Box<dyn std::error::Error>> {
+    let body = registry.fetch_crate(name)?;
+    parse_crates_io_body(&body, version, rust_version, date_field)
+}
+
+#[cfg(feature = "async")]
+pub async fn get_latest_version_async(
+    client: &reqwest::Client,
+    name: &str,
+    version: &str,
+    rust_version: Option<&Version>,
+    date_field: DateField,
+) -> Result<CratesIoResponse, Box<dyn std::error::Error>> {
+    let body = client
+        .get(get_crates_io_url(name))
+        .send()
+        .await?
+        .bytes()
+        .await?;
+
+    parse_crates_io_body(&body, version, rust_version, date_field)
+}
+
+/// RustSec advisory IDs from an OSV query response, falling back to the OSV ID when a
+/// vulnerability has no RustSec alias
+fn advisories_from_value(value: serde_json::Value) -> Vec<String> {
+    let Some(vulns) = value.get("vulns").and_then(|v| v.as_array()) else {
+        return vec![];
     };
 
-    Ok(CratesIoResponse::from_value(response, version))
+    vulns
+        .iter()
+        .flat_map(|vuln| {
+            let id = vuln.get("id")?.as_str()?;
+            let rustsec_id = std::iter::once(id)
+                .chain(
+                    vuln.get("aliases")
+                        .and_then(|a| a.as_array())
+                        .into_iter()
+                        .flatten()
+                        .flat_map(|a| a.as_str()),
+                )
+                .find(|id| id.starts_with("RUSTSEC-"));
+
+            Some(rustsec_id.unwrap_or(id).to_string())
+        })
+        .collect()
+}
+
+/// Known security advisories affecting the given version of a crate, from the OSV database which
+/// includes the RustSec advisory database - https://google.github.io/osv.dev/post-v1-query/
+pub fn get_advisories(
+    registry: &dyn Registry,
+    name: &str,
+    version: &str,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let body = registry.fetch_advisories(name, version)?;
+    Ok(advisories_from_value(parse_body(&body)?))
+}
+
+#[cfg(feature = "async")]
+pub async fn get_advisories_async(
+    client: &reqwest::Client,
+    name: &str,
+    version: &str,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let body = client
+        .post(OSV_QUERY_URL)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(get_osv_query(name, version))
+        .send()
+        .await?
+        .bytes()
+        .await?;
+
+    Ok(advisories_from_value(parse_body(&body)?))
 }
 
 #[cfg(test)]
@@ -110,6 +432,8 @@ mod tests {
                 "repository": "\thttps://github.com/user/repo ",
                 "description": " A\ndescription\n ",
                 "max_stable_version": "0.2.0",
+                "downloads": 1234567,
+                "recent_downloads": 890,
             },
             "versions": [
                 {
@@ -118,13 +442,15 @@ mod tests {
                 },
                 {
                     "num": "0.2.0",
-                    "updated_at": "2023-07-02T00:00:00Z"
+                    "updated_at": "2023-07-02T00:00:00Z",
+                    "rust_version": "1.70"
                 },
                 {}
             ]
         });
 
-        let response = CratesIoResponse::from_value(response, "0.1.0");
+        let response =
+            CratesIoResponse::from_value(response, "0.1.0", None, DateField::Updated).unwrap();
 
         assert_eq!(
             response.repository,
@@ -140,18 +466,225 @@ mod tests {
             response.current_version_date,
             Some("2023-07-01T00:00:00Z".to_string())
         );
+        assert_eq!(response.downloads, Some(1234567));
+        assert_eq!(response.recent_downloads, Some(890));
+        assert!(response.intermediate_versions.is_empty());
+        assert_eq!(response.latest_rust_version, Some("1.70".to_string()));
     }
 
     #[test]
-    fn test_crates_io_empty_response() {
-        let response = serde_json::json!({});
+    fn test_crates_io_response_from_value_with_rust_version() {
+        let response = serde_json::json!({
+            "crate": {
+                "max_stable_version": "0.4.0",
+            },
+            "versions": [
+                { "num": "0.5.0-beta.1", "rust_version": "1.60" },
+                { "num": "0.4.0", "rust_version": "1.75" },
+                { "num": "0.3.1", "rust_version": "1.70", "yanked": true },
+                { "num": "0.3.0", "rust_version": "1.70.0" },
+                { "num": "0.2.0" },
+                { "num": "0.1.0", "rust_version": "1.56" }
+            ]
+        });
 
-        let response = CratesIoResponse::from_value(response, "0.1.0");
+        let response = CratesIoResponse::from_value(
+            response,
+            "0.1.0",
+            Some(&Version::new(1, 70, 0)),
+            DateField::Updated,
+        )
+        .unwrap();
+        assert_eq!(response.latest_version, "0.3.0");
+    }
 
-        assert_eq!(response.repository, None);
-        assert_eq!(response.description, None);
+    #[test]
+    fn test_crates_io_response_from_value_without_compatible_version() {
+        let response = serde_json::json!({
+            "crate": {
+                "max_stable_version": "0.2.0",
+            },
+            "versions": [
+                { "num": "0.2.0", "rust_version": "1.75" },
+                { "num": "0.1.0", "rust_version": "1.70" }
+            ]
+        });
+
+        let response = CratesIoResponse::from_value(
+            response,
+            "0.1.0",
+            Some(&Version::new(1, 50, 0)),
+            DateField::Updated,
+        )
+        .unwrap();
         assert_eq!(response.latest_version, "0.1.0");
-        assert_eq!(response.latest_version_date, None);
-        assert_eq!(response.current_version_date, None);
+    }
+
+    #[test]
+    fn test_crates_io_response_from_value_with_created_date() {
+        let response = serde_json::json!({
+            "crate": { "max_stable_version": "0.2.0" },
+            "versions": [
+                {
+                    "num": "0.1.0",
+                    "created_at": "2023-01-01T00:00:00Z",
+                    "updated_at": "2023-07-01T00:00:00Z"
+                },
+                {
+                    "num": "0.2.0",
+                    "created_at": "2023-02-01T00:00:00Z",
+                    "updated_at": "2023-07-02T00:00:00Z"
+                }
+            ]
+        });
+
+        let response =
+            CratesIoResponse::from_value(response, "0.1.0", None, DateField::Created).unwrap();
+        assert_eq!(
+            response.latest_version_date,
+            Some("2023-02-01T00:00:00Z".to_string())
+        );
+        assert_eq!(
+            response.current_version_date,
+            Some("2023-01-01T00:00:00Z".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_crates_io_body_with_errors() {
+        let error = |body: &str| {
+            parse_crates_io_body(body.as_bytes(), "0.1.0", None, DateField::Updated)
+                .err()
+                .map(|e| e.to_string())
+        };
+
+        assert_eq!(
+            error(r#"{"errors":[{"detail":"crate `foo` does not exist"}]}"#),
+            Some("crate `foo` does not exist".to_string())
+        );
+        assert_eq!(
+            error(r#"{"errors":[{"detail":"first"},{"detail":" second\n"}]}"#),
+            Some("first, second".to_string())
+        );
+        assert_eq!(error(r#"{"errors":[]}"#), Some("unknown error".to_string()));
+        assert!(error("not json").is_some());
+        assert_eq!(error(r#"{"crate":{"max_stable_version":"0.2.0"}}"#), None);
+        assert!(error(r#"{"crate":{"max_stable_version":"latest"}}"#)
+            .is_some_and(|e| e.starts_with("invalid latest version \"latest\"")));
+        assert_eq!(
+            error(""),
+            Some("no crate in the crates.io response".to_string())
+        );
+    }
+
+    #[test]
+    fn test_get_intermediate_versions() {
+        let versions = serde_json::json!([
+            { "num": "0.4.0" },
+            { "num": "0.3.1", "yanked": true },
+            { "num": "0.3.0" },
+            { "num": "0.3.0-beta.1" },
+            { "num": "0.2.1" },
+            { "num": "0.2.0" },
+            { "num": "0.1.0" }
+        ]);
+        let versions = versions.as_array();
+
+        assert_eq!(
+            get_intermediate_versions(versions, "0.1.0", "0.4.0"),
+            vec!["0.2.0", "0.2.1", "0.3.0"]
+        );
+        assert!(get_intermediate_versions(versions, "0.4.0", "0.4.0").is_empty());
+        assert!(get_intermediate_versions(versions, "0.1", "0.4.0").is_empty());
+        assert!(get_intermediate_versions(None, "0.1.0", "0.4.0").is_empty());
+    }
+
+    #[test]
+    fn test_get_yanked_versions() {
+        let versions = serde_json::json!([
+            { "num": "0.4.0" },
+            { "num": "0.3.2", "yanked": true },
+            { "num": "0.3.1", "yanked": true },
+            { "num": "0.3.1-beta.1", "yanked": true },
+            { "num": "0.3.0" },
+            { "num": "0.1.1", "yanked": true },
+            { "num": "0.1.0" }
+        ]);
+        let versions = versions.as_array();
+
+        assert_eq!(
+            get_yanked_versions(versions, "0.1.0", "0.4.0"),
+            vec!["0.1.1", "0.3.1", "0.3.2"]
+        );
+        assert_eq!(
+            get_yanked_versions(versions, "0.3.0", "0.4.0"),
+            vec!["0.3.1", "0.3.2"]
+        );
+        assert!(get_yanked_versions(versions, "0.4.0", "0.4.0").is_empty());
+        assert!(get_yanked_versions(None, "0.1.0", "0.4.0").is_empty());
+
+        assert!(is_version_yanked(versions, "0.3.1"));
+        assert!(!is_version_yanked(versions, "0.3.0"));
+        assert!(!is_version_yanked(versions, "0.2.0"));
+        assert!(!is_version_yanked(None, "0.3.1"));
+    }
+
+    #[test]
+    fn test_advisories_from_value() {
+        let response = serde_json::json!({
+            "vulns": [
+                { "id": "RUSTSEC-2023-0001", "aliases": ["CVE-2023-0001"] },
+                { "id": "GHSA-xxxx-yyyy", "aliases": ["CVE-2023-0002", "RUSTSEC-2023-0002"] },
+                { "id": "GHSA-zzzz-wwww" },
+                {}
+            ]
+        });
+
+        assert_eq!(
+            advisories_from_value(response),
+            vec!["RUSTSEC-2023-0001", "RUSTSEC-2023-0002", "GHSA-zzzz-wwww"]
+        );
+        assert!(advisories_from_value(serde_json::json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_get_headers() {
+        let headers = |headers: List| {
+            headers
+                .iter()
+                .map(|h| String::from_utf8_lossy(h).to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            headers(get_headers("tool (contact@example.com)", None).unwrap()),
+            vec!["User-Agent: tool (contact@example.com)"]
+        );
+        assert!(get_default_user_agent().starts_with("cargo-interactive-update ("));
+        assert_eq!(
+            headers(get_headers(&get_default_user_agent(), Some("application/json")).unwrap()),
+            vec![
+                format!("User-Agent: {}", get_default_user_agent()),
+                "Content-Type: application/json".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_rust_version() {
+        assert_eq!(parse_rust_version("1.70"), Some(Version::new(1, 70, 0)));
+        assert_eq!(parse_rust_version(" 1.70.1 "), Some(Version::new(1, 70, 1)));
+        assert_eq!(parse_rust_version("1"), None);
+        assert_eq!(parse_rust_version("stable"), None);
+    }
+
+    #[test]
+    fn test_crates_io_empty_response() {
+        let response = serde_json::json!({});
+
+        assert_eq!(
+            CratesIoResponse::from_value(response, "0.1.0", None, DateField::Updated).err(),
+            Some("no crate in the crates.io response".to_string())
+        );
     }
 }
@@ -1,4 +1,12 @@
+use cargo_interactive_update::{
+    ApplyOptions, Config, DateField, DependencyKind, IgnoredVersion, ScanOptions, SortOrder,
+    UpdateType,
+};
 use clap::Parser;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 #[derive(Parser)]
 #[command(name = "cargo", bin_name = "cargo", styles = clap_cargo::style::CLAP_STYLING)]
@@ -6,8 +14,22 @@ pub enum CargoCli {
     InteractiveUpdate(Args),
 }
 
+/// Non-interactive output of the outdated dependencies
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// GitHub-flavored Markdown table, e.g. for a pull request description
+    Markdown,
+}
+
+const EXIT_CODES_HELP: &str = "Exit codes:
+  0  All dependencies are up to date, or the selected updates were applied
+  1  An error occurred
+  2  Quit without applying any update
+  3  Some dependencies are outdated, with `--check`
+  4  Some dependencies could not be checked, with `--strict`";
+
 #[derive(clap::Args)]
-#[command(version, about, author, long_about = None)]
+#[command(version, about, author, long_about = None, after_help = EXIT_CODES_HELP)]
 pub struct Args {
     /// Selects all dependencies to be updated
     #[arg(short, long)]
@@ -17,6 +39,34 @@ pub struct Args {
     #[arg(short, long)]
     pub yes: bool,
 
+    /// Also apply major (breaking) updates when using `--yes`
+    #[arg(long, requires = "yes")]
+    pub allow_major: bool,
+
+    /// Number of updates applied with `--yes` above which the plan is printed and a confirmation is asked, as with any major update
+    #[arg(long, value_name = "N", default_value_t = 25)]
+    pub max_changes: usize,
+
+    /// Apply the updates of `--yes` without asking for confirmation, however many or breaking they are
+    #[arg(long, requires = "yes")]
+    pub force: bool,
+
+    /// Selects exactly the updates of the given type, leaving the others to review
+    #[arg(long, value_enum, value_name = "TYPE", conflicts_with_all = ["all", "security_only"])]
+    pub preselect: Option<UpdateType>,
+
+    /// Check the dependencies again after applying the selected updates, and review the ones still outdated until none is left or quitting
+    #[arg(long = "loop", conflicts_with_all = ["yes", "check", "list", "format", "output_patch", "branch"])]
+    pub keep_going: bool,
+
+    /// Select the dependencies whose current version was yanked, to update them to the nearest version that wasn't, within their requirement if possible
+    #[arg(long)]
+    pub include_yanked_current: bool,
+
+    /// Ignore the selection saved when quitting the last run
+    #[arg(short, long)]
+    pub fresh: bool,
+
     /// Don't run `cargo check` after updating
     #[arg(short, long)]
     pub no_check: bool,
@@ -24,4 +74,302 @@ pub struct Args {
     /// Pin dependencies to exact versions, with an `=` prefix
     #[arg(short, long)]
     pub pin: bool,
+
+    /// Write and report the updates of each kind of dependencies (normal, dev, build, workspace) separately
+    #[arg(long)]
+    pub split_by_kind: bool,
+
+    /// Create a git commit with the updated Cargo.toml files, one for each kind of dependencies with `--split-by-kind`
+    #[arg(long)]
+    pub commit: bool,
+
+    /// Create a new git branch and switch to it before updating
+    #[arg(long, value_name = "NAME")]
+    pub branch: Option<String>,
+
+    /// Write the changes as a patch to this file, to apply with `git apply` from the directory of the Cargo.toml file, instead of updating the Cargo.toml files
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["commit", "branch"])]
+    pub output_patch: Option<PathBuf>,
+
+    /// Write the updated Cargo.toml files under this directory, keeping the layout of the workspace members, instead of updating them in place
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["commit", "branch", "output_patch", "keep_going"])]
+    pub output_dir: Option<PathBuf>,
+
+    /// Only update Cargo.lock to the versions allowed by the current requirements, leaving the Cargo.toml files untouched and skipping the updates that need a new requirement
+    #[arg(long, conflicts_with_all = ["pin", "split_by_kind", "commit", "output_patch", "output_dir"])]
+    pub lock_only: bool,
+
+    /// Only list outdated dependencies and exit with a non-zero code if there are any
+    #[arg(short, long, conflicts_with_all = ["all", "yes"])]
+    pub check: bool,
+
+    /// Only print the outdated dependencies, one per line as `name current -> latest (kind)`, and always exit successfully
+    #[arg(short, long, conflicts_with_all = ["all", "yes", "check", "security_only"])]
+    pub list: bool,
+
+    /// Only print the outdated dependencies in the given format, and always exit successfully
+    #[arg(long, value_enum, conflicts_with_all = ["all", "yes", "check", "list", "security_only"])]
+    pub format: Option<OutputFormat>,
+
+    /// Only check the dependencies pinned to an exact version with `=`, keeping the `=` when updating them
+    #[arg(long)]
+    pub pinned_only: bool,
+
+    /// Look up the known security advisories affecting the current version of the outdated dependencies, sending their names and versions to OSV (api.osv.dev)
+    #[arg(long)]
+    pub advisories: bool,
+
+    /// Only select the dependencies with a known security advisory affecting their current version, implies --advisories
+    #[arg(long, conflicts_with = "all")]
+    pub security_only: bool,
+
+    /// Order of the dependencies within each kind
+    #[arg(long, value_enum, default_value_t)]
+    pub sort: SortOrder,
+
+    /// Timestamp of crates.io shown as the date of the versions, and used to sort them by age
+    #[arg(long, value_enum, default_value_t, value_name = "FIELD")]
+    pub date_field: DateField,
+
+    /// Also display the dependencies that are already up to date, for reference
+    #[arg(short, long)]
+    pub show_current: bool,
+
+    /// Never report the given dependency as outdated, can be used multiple times
+    #[arg(short, long, value_name = "CRATE")]
+    pub exclude: Vec<String>,
+
+    /// Never suggest the given version of a dependency, or all the versions up to it with `<=`, falling back to the newest other one, can be used multiple times
+    #[arg(long, value_name = "CRATE@VERSION")]
+    pub ignore: Vec<IgnoredVersion>,
+
+    /// Levels of workspace members to check the dependencies of, 0 only checking the root package
+    #[arg(long, value_name = "N")]
+    pub depth: Option<usize>,
+
+    /// Leave out the dev dependencies
+    #[arg(long)]
+    pub no_dev: bool,
+
+    /// Leave out the build dependencies
+    #[arg(long)]
+    pub no_build: bool,
+
+    /// Leave out the dependencies declared in `[workspace.dependencies]`
+    #[arg(long)]
+    pub no_workspace: bool,
+
+    /// Suggest the latest versions even if they require a newer Rust version than the `rust-version` of the package
+    #[arg(long)]
+    pub ignore_rust_version: bool,
+
+    /// Also list the outdated packages of Cargo.lock that are not direct dependencies, which can't be selected
+    #[arg(long)]
+    pub include_transitive: bool,
+
+    /// User-Agent sent to crates.io, e.g. to include a contact, instead of the name and repository of this tool
+    #[arg(long, env = "CARGO_INTERACTIVE_UPDATE_UA")]
+    pub user_agent: Option<String>,
+
+    /// Stop checking dependencies after this many seconds, the direct ones first and then the transitive ones, and review the ones checked so far
+    #[arg(long, value_name = "SECONDS")]
+    pub max_time: Option<u64>,
+
+    /// Exit with a non-zero code, before reviewing or updating anything, if any dependency could not be checked
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Assert that Cargo.lock will remain unchanged, passed to `cargo check` after updating so that it fails if Cargo.lock needs to be updated, not allowed with `--lock-only`
+    #[arg(long)]
+    pub locked: bool,
+
+    /// Equivalent to specifying both `--locked` and `--offline`, passed to `cargo check` after updating, crates.io still being reached to check the dependencies, not allowed with `--lock-only`
+    #[arg(long)]
+    pub frozen: bool,
+
+    #[command(flatten)]
+    pub manifest: clap_cargo::Manifest,
+}
+
+impl Args {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(manifest_path) = &self.manifest.manifest_path {
+            if manifest_path.file_name() != Some("Cargo.toml".as_ref()) {
+                return Err("the manifest-path must be a path to a Cargo.toml file".to_string());
+            }
+        }
+
+        if self.lock_only && (self.locked || self.frozen) {
+            return Err(format!(
+                "`{}` can't be used with `--lock-only`, which updates Cargo.lock",
+                if self.locked { "--locked" } else { "--frozen" }
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Directory containing the Cargo.toml file to update, defaults to the current directory
+    pub fn manifest_dir(&self) -> &Path {
+        self.manifest
+            .manifest_path
+            .as_deref()
+            .and_then(Path::parent)
+            // `Cargo.toml` alone has an empty parent, which can't be used as a working directory
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+    }
+
+    /// Kinds of dependencies left out with `--no-dev`, `--no-build` and `--no-workspace`
+    pub fn excluded_kinds(&self) -> Vec<DependencyKind> {
+        [
+            (self.no_dev, DependencyKind::Dev),
+            (self.no_build, DependencyKind::Build),
+            (self.no_workspace, DependencyKind::Workspace),
+        ]
+        .into_iter()
+        .filter_map(|(excluded, kind)| excluded.then_some(kind))
+        .collect()
+    }
+
+    pub fn scan_options(&self) -> ScanOptions {
+        ScanOptions {
+            manifest_dir: self.manifest_dir().to_path_buf(),
+            ignore_rust_version: self.ignore_rust_version,
+            check_advisories: self.advisories || self.security_only,
+            exclude: self.exclude.clone(),
+            ignore: self.ignore.clone(),
+            exclude_kinds: self.excluded_kinds(),
+            max_time: self.max_time.map(Duration::from_secs),
+            include_transitive: self.include_transitive,
+            user_agent: self.user_agent.clone(),
+            date_field: self.date_field,
+            include_yanked_current: self.include_yanked_current,
+            pinned_only: self.pinned_only,
+            depth: self.depth,
+        }
+    }
+
+    pub fn apply_options(&self, config: &Config) -> ApplyOptions {
+        ApplyOptions {
+            manifest_dir: self.manifest_dir().to_path_buf(),
+            pin: self.pin || config.pin,
+            check: !(self.no_check || config.no_check),
+            split_by_kind: self.split_by_kind,
+            commit: self.commit,
+            branch: self.branch.clone(),
+            output_patch: self.output_patch.clone(),
+            output_dir: self.output_dir.clone(),
+            lock_only: self.lock_only,
+            locked: self.locked,
+            frozen: self.frozen,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Args {
+        let CargoCli::InteractiveUpdate(args) =
+            CargoCli::parse_from(["cargo", "interactive-update"].iter().chain(args));
+        args
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(parse_args(&[]).validate().is_ok());
+        assert!(parse_args(&["--check", "--locked"]).validate().is_ok());
+        assert!(parse_args(&["--manifest-path", "foo/Cargo.toml"])
+            .validate()
+            .is_ok());
+        assert!(parse_args(&["--locked"]).validate().is_ok());
+        assert!(parse_args(&["--list", "--locked"]).validate().is_ok());
+        assert!(parse_args(&["--output-dir", "preview", "--locked"])
+            .validate()
+            .is_ok());
+        assert!(parse_args(&["--frozen"]).validate().is_ok());
+        assert!(parse_args(&["--lock-only", "--locked"]).validate().is_err());
+        assert!(parse_args(&["--lock-only", "--frozen"]).validate().is_err());
+        assert!(parse_args(&["--manifest-path", "foo/Other.toml"])
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_excluded_kinds() {
+        assert!(parse_args(&[]).excluded_kinds().is_empty());
+        assert!(parse_args(&["--no-dev"]).excluded_kinds() == [DependencyKind::Dev]);
+        assert!(parse_args(&["--no-build"]).excluded_kinds() == [DependencyKind::Build]);
+        assert!(parse_args(&["--no-workspace"]).excluded_kinds() == [DependencyKind::Workspace]);
+        assert!(
+            parse_args(&["--no-dev", "--no-build"]).excluded_kinds()
+                == [DependencyKind::Dev, DependencyKind::Build]
+        );
+        assert!(
+            parse_args(&["--no-workspace", "--no-dev", "--no-build"]).excluded_kinds()
+                == [
+                    DependencyKind::Dev,
+                    DependencyKind::Build,
+                    DependencyKind::Workspace
+                ]
+        );
+    }
+
+    #[test]
+    fn test_preselect() {
+        assert_eq!(parse_args(&[]).preselect, None);
+        assert_eq!(
+            parse_args(&["--preselect", "patch"]).preselect,
+            Some(UpdateType::Patch)
+        );
+        assert!(CargoCli::try_parse_from([
+            "cargo",
+            "interactive-update",
+            "--preselect",
+            "patch",
+            "--all"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_loop() {
+        assert!(!parse_args(&[]).keep_going);
+        assert!(parse_args(&["--loop"]).keep_going);
+        assert!(
+            CargoCli::try_parse_from(["cargo", "interactive-update", "--loop", "--yes"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(parse_args(&[]).format, None);
+        assert_eq!(
+            parse_args(&["--format", "markdown"]).format,
+            Some(OutputFormat::Markdown)
+        );
+        assert!(CargoCli::try_parse_from([
+            "cargo",
+            "interactive-update",
+            "--format",
+            "markdown",
+            "--list"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_manifest_dir() {
+        assert_eq!(parse_args(&[]).manifest_dir(), Path::new("."));
+        assert_eq!(
+            parse_args(&["--manifest-path", "foo/Cargo.toml"]).manifest_dir(),
+            Path::new("foo")
+        );
+        assert_eq!(
+            parse_args(&["--manifest-path", "Cargo.toml"]).manifest_dir(),
+            Path::new(".")
+        );
+    }
 }
@@ -1,10 +1,19 @@
-use semver::Version;
-use std::collections::HashMap;
+use semver::{BuildMetadata, Op, Version, VersionReq};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Component, Path, PathBuf},
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
 use toml_edit::{DocumentMut, Item, Value};
 
 use crate::{
     api,
-    dependency::{Dependencies, Dependency, DependencyKind},
+    dependency::{DateField, Dependencies, Dependency, DependencyKind},
+    Error,
 };
 
 #[derive(Clone, PartialEq, Eq, Default)]
@@ -15,34 +24,90 @@ pub struct CargoDependency {
 }
 
 impl CargoDependency {
-    fn get_latest_version_wrapper(
+    fn to_dependency(
         &self,
-        package_name: Option<String>,
-        workspace_path: Option<String>,
+        response: &api::CratesIoResponse,
+        advisories: &[String],
+        package_name: &str,
+        workspace_path: Option<&str>,
+        rust_version: Option<&Version>,
     ) -> Option<Dependency> {
-        let parsed_current_version = Version::parse(&self.version).ok()?;
-
-        let response = api::get_latest_version(self).expect("Unable to reach crates.io");
-
-        let parsed_latest_version =
-            Version::parse(&response.latest_version).expect("Latest version is not a valid semver");
-
-        if parsed_current_version < parsed_latest_version {
-            Some(Dependency {
-                name: self.name.to_string(),
-                current_version: self.version.to_string(),
-                latest_version: response.latest_version,
-                repository: response.repository,
-                latest_version_date: response.latest_version_date,
-                current_version_date: response.current_version_date,
-                description: response.description,
-                kind: self.kind,
-                package_name,
-                workspace_path,
+        let parsed_current_version = get_base_version(&self.version)?;
+        // Only kept when updating would force raising the `rust-version` of the package
+        let latest_rust_version = response.latest_rust_version.clone().filter(|latest| {
+            rust_version.is_some_and(|rust_version| {
+                api::parse_rust_version(latest).is_some_and(|latest| &latest > rust_version)
             })
-        } else {
-            None
-        }
+        });
+
+        Some(Dependency {
+            name: self.name.to_string(),
+            requirement: self.version.to_string(),
+            current_version: parsed_current_version.to_string(),
+            latest_version: response.latest_version.clone(),
+            repository: response.repository.clone(),
+            latest_version_date: response.latest_version_date.clone(),
+            current_version_date: response.current_version_date.clone(),
+            description: response.description.clone(),
+            downloads: response.downloads,
+            recent_downloads: response.recent_downloads,
+            intermediate_versions: response.intermediate_versions.clone(),
+            yanked_versions: response.yanked_versions.clone(),
+            latest_rust_version,
+            rust_version: rust_version.map(Version::to_string),
+            current_version_yanked: response.current_version_yanked,
+            operator: None,
+            advisories: advisories.to_vec(),
+            kind: self.kind,
+            patched: false,
+            package_name: Some(package_name.to_string()),
+            workspace_path: workspace_path.map(str::to_string),
+        })
+    }
+}
+
+/// Latest version of a dependency along with the advisories affecting its current version, or
+/// the reason it could not be fetched
+type Fetched = Result<(api::CratesIoResponse, Vec<String>), String>;
+
+/// Name and base version of a crate to fetch, along with the `rust-version` its latest version
+/// must be compatible with, as the members of a workspace can declare different ones
+type FetchKey = (String, String, Option<Version>);
+
+/// Maximum number of crates fetched at the same time, to keep the load on crates.io reasonable
+/// when checking every package of a Cargo.lock file
+const MAX_CONCURRENT_FETCHES: usize = 16;
+
+/// Dependencies split by whether they are outdated, after retrieving their latest versions
+#[derive(Default)]
+pub struct RetrievedDependencies {
+    pub outdated: Dependencies,
+    pub up_to_date: Vec<Dependency>,
+    /// Dependencies requiring a version newer than the latest one
+    pub ahead: Vec<Dependency>,
+    /// Number of dependencies not checked because the time budget elapsed
+    pub unchecked: usize,
+    /// Names of the dependencies whose latest version could not be fetched, with the reason
+    pub failed: Vec<(String, String)>,
+}
+
+/// A dependency as declared in a specific package of the workspace
+struct DependencyOccurrence<'a> {
+    dependency: &'a CargoDependency,
+    package_name: &'a str,
+    workspace_path: Option<&'a str>,
+    /// `rust-version` of the package declaring the dependency
+    rust_version: Option<&'a Version>,
+}
+
+impl DependencyOccurrence<'_> {
+    /// Key of the fetch of the dependency, none when its requirement has no single lowest version
+    fn fetch_key(&self, ignore_rust_version: bool) -> Option<FetchKey> {
+        Some((
+            self.dependency.name.clone(),
+            get_base_version(&self.dependency.version)?.to_string(),
+            self.rust_version.filter(|_| !ignore_rust_version).cloned(),
+        ))
     }
 }
 
@@ -50,69 +115,439 @@ impl CargoDependency {
 pub struct CargoDependencies {
     pub cargo_toml: DocumentMut,
     package_name: String,
+    rust_version: Option<Version>,
     dependencies: Vec<CargoDependency>,
+    /// Names of the dependencies declared without a version requirement, e.g. path or git ones
+    without_version: Vec<String>,
+    /// Names of the dependencies from another registry than crates.io, e.g. `registry = "my-corp"`
+    from_other_registry: Vec<String>,
     workspace_members: HashMap<String, Box<CargoDependencies>>,
 }
 
 impl CargoDependencies {
-    pub fn gather_dependencies(relative_path: &str) -> Self {
-        let cargo_toml = read_cargo_file(relative_path);
+    /// Reads the dependencies of the package and, down to the given depth if any, the ones of its
+    /// workspace members, `Some(0)` leaving the members out. Fails when one of their Cargo.toml
+    /// files can't be read or parsed
+    pub fn gather_dependencies(manifest_dir: &Path, depth: Option<usize>) -> Result<Self, Error> {
+        let cargo_toml = read_cargo_file(manifest_dir)?;
         let package_name = get_package_name(&cargo_toml);
+        let rust_version = get_rust_version(&cargo_toml);
         let dependencies = get_cargo_dependencies(&cargo_toml);
-        let workspace_members = get_workspace_members(&cargo_toml);
+        let without_version = get_dependencies_without_version(&cargo_toml);
+        let from_other_registry = get_dependencies_from_other_registries(&cargo_toml);
+        let mut workspace_members = get_workspace_members(&cargo_toml, manifest_dir, depth)?;
+        for member in workspace_members.values_mut() {
+            member.inherit_rust_version(rust_version.as_ref());
+        }
 
-        Self {
+        Ok(Self {
             cargo_toml,
             package_name,
+            rust_version,
             dependencies,
+            without_version,
+            from_other_registry,
             workspace_members,
+        })
+    }
+
+    /// Sets the `rust-version` of the package and of its members which don't declare their own,
+    /// e.g. inheriting it with `rust-version.workspace = true`, to the one of the workspace
+    fn inherit_rust_version(&mut self, rust_version: Option<&Version>) {
+        if self.rust_version.is_none() {
+            self.rust_version = rust_version.cloned();
+        }
+
+        for member in self.workspace_members.values_mut() {
+            member.inherit_rust_version(self.rust_version.as_ref());
         }
     }
 
-    pub fn retrieve_outdated_dependencies(self, workspace_path: Option<String>) -> Dependencies {
-        let mut direct_dependencies_threads = Vec::new();
-        let mut workspace_member_threads = Vec::new();
-        let mut cargo_toml_files = HashMap::new();
+    /// Retrieves the latest versions of the dependencies, returning the outdated ones along with
+    /// the ones that are already up to date and the ones that could not be checked, either
+    /// because their fetch failed or because the deadline passed first. The security advisories
+    /// of the outdated ones are only looked up with `check_advisories`
+    pub fn retrieve_outdated_dependencies(
+        self,
+        workspace_path: Option<String>,
+        ignore_rust_version: bool,
+        check_advisories: bool,
+        date_field: DateField,
+        deadline: Option<Instant>,
+        user_agent: &str,
+    ) -> RetrievedDependencies {
+        let registry = Arc::new(api::CratesIo {
+            user_agent: user_agent.to_string(),
+        });
 
-        cargo_toml_files.insert(
-            workspace_path.clone().unwrap_or_else(|| ".".to_string()),
-            self.cargo_toml,
+        self.retrieve_outdated_dependencies_from(
+            registry,
+            workspace_path,
+            ignore_rust_version,
+            check_advisories,
+            date_field,
+            deadline,
+        )
+    }
+
+    /// Same as [`Self::retrieve_outdated_dependencies`], fetching from the given registry
+    fn retrieve_outdated_dependencies_from(
+        self,
+        registry: Arc<dyn api::Registry>,
+        workspace_path: Option<String>,
+        ignore_rust_version: bool,
+        check_advisories: bool,
+        date_field: DateField,
+        deadline: Option<Instant>,
+    ) -> RetrievedDependencies {
+        // The same crate at the same version is only fetched once, even if it is declared in
+        // multiple workspace members with the same `rust-version`
+        let unique_dependencies =
+            self.get_unique_dependencies(workspace_path.as_deref(), ignore_rust_version);
+        let (fetches, unchecked) = fetch_latest_versions(
+            registry,
+            unique_dependencies,
+            check_advisories,
+            date_field,
+            deadline,
         );
-        for dependency in self.dependencies.iter() {
-            let dependency = dependency.clone();
-            let package_name = self.package_name.to_string();
-            let workspace_path = workspace_path.clone();
-            direct_dependencies_threads.push(std::thread::spawn(move || {
-                dependency.get_latest_version_wrapper(Some(package_name), workspace_path)
-            }));
-        }
 
-        for (member, dependencies) in self.workspace_members.iter() {
-            let dependencies = dependencies.clone();
-            let member = member.clone();
-            workspace_member_threads.push(std::thread::spawn(move || {
-                dependencies.retrieve_outdated_dependencies(Some(member))
-            }));
-        }
+        let mut retrieved = self.into_dependencies(workspace_path, fetches, ignore_rust_version);
+        retrieved.unchecked = unchecked;
+        retrieved
+    }
+
+    /// Retrieves the latest versions of the crates.io packages of the Cargo.lock file that are not
+    /// direct dependencies, returning the outdated ones, which can't be updated by editing a
+    /// Cargo.toml file
+    pub fn retrieve_outdated_transitive_dependencies(
+        &self,
+        manifest_dir: &Path,
+        ignore_rust_version: bool,
+        check_advisories: bool,
+        date_field: DateField,
+        deadline: Option<Instant>,
+        user_agent: &str,
+    ) -> RetrievedDependencies {
+        let Some(cargo_lock) = read_cargo_lock_file(manifest_dir) else {
+            return RetrievedDependencies::default();
+        };
+
+        let mut occurrences = Vec::new();
+        self.collect_occurrences(None, &mut occurrences);
+        let direct_dependencies = occurrences
+            .iter()
+            .map(|occurrence| occurrence.dependency.name.as_str())
+            .collect::<HashSet<_>>();
+
+        // The packages of Cargo.lock are shared by the whole workspace, whose `rust-version` they
+        // must be compatible with
+        let rust_version = self.rust_version.clone().filter(|_| !ignore_rust_version);
+        let packages = get_crates_io_packages(&cargo_lock)
+            .into_iter()
+            .filter(|(name, _)| !direct_dependencies.contains(name.as_str()))
+            .map(|(name, version)| (name, version, rust_version.clone()))
+            .collect::<HashSet<_>>();
+
+        let registry = Arc::new(api::CratesIo {
+            user_agent: user_agent.to_string(),
+        });
+        let (fetches, unchecked) =
+            fetch_latest_versions(registry, packages, check_advisories, date_field, deadline);
 
-        let mut dependencies = direct_dependencies_threads
+        let mut failed = Vec::new();
+        let mut dependencies = fetches
             .into_iter()
-            .flat_map(|t| t.join())
-            .flatten()
+            .filter_map(|((name, version, _), fetched)| match fetched {
+                Ok((response, advisories)) => {
+                    let dependency = CargoDependency {
+                        name,
+                        version,
+                        kind: DependencyKind::Normal,
+                    };
+                    let mut dependency =
+                        dependency.to_dependency(&response, &advisories, "", None, None)?;
+                    dependency.package_name = None;
+                    Some(dependency)
+                }
+                Err(reason) => {
+                    failed.push((name, reason));
+                    None
+                }
+            })
+            .filter(|dependency| {
+                is_outdated(&dependency.current_version, &dependency.latest_version)
+            })
             .collect::<Vec<_>>();
+        dependencies.sort();
+        failed.sort();
+
+        RetrievedDependencies {
+            outdated: Dependencies::new(dependencies, HashMap::new()),
+            up_to_date: vec![],
+            ahead: vec![],
+            unchecked,
+            failed,
+        }
+    }
+
+    /// Same as [`Self::retrieve_outdated_dependencies`], fetching concurrently on the current
+    /// async runtime instead of on a pool of threads
+    #[cfg(feature = "async")]
+    pub async fn retrieve_outdated_dependencies_async(
+        self,
+        workspace_path: Option<String>,
+        ignore_rust_version: bool,
+        check_advisories: bool,
+        date_field: DateField,
+        user_agent: &str,
+    ) -> RetrievedDependencies {
+        let client = reqwest::Client::builder()
+            .user_agent(user_agent)
+            .build()
+            .unwrap_or_default();
+
+        let fetches = self
+            .get_unique_dependencies(workspace_path.as_deref(), ignore_rust_version)
+            .into_iter()
+            .map(|key| {
+                let client = &client;
+                async move {
+                    let (name, version, rust_version) = &key;
+                    let fetched = match api::get_latest_version_async(
+                        client,
+                        name,
+                        version,
+                        rust_version.as_ref(),
+                        date_field,
+                    )
+                    .await
+                    {
+                        Ok(response)
+                            if check_advisories
+                                && is_outdated(version, &response.latest_version) =>
+                        {
+                            api::get_advisories_async(client, name, version)
+                                .await
+                                .map(|advisories| (response, advisories))
+                                .map_err(|e| format_advisories_error(&*e))
+                        }
+                        Ok(response) => Ok((response, vec![])),
+                        Err(e) => Err(e.to_string()),
+                    };
+
+                    (key, fetched)
+                }
+            });
 
-        workspace_member_threads
+        let fetches = futures_util::future::join_all(fetches)
+            .await
             .into_iter()
-            .for_each(|workspace_dependencies| {
-                let _ = workspace_dependencies.join().map(|workspace_dependencies| {
-                    dependencies.extend(workspace_dependencies.dependencies);
-                    cargo_toml_files.extend(workspace_dependencies.cargo_toml_files);
-                });
+            .collect::<HashMap<_, _>>();
+
+        self.into_dependencies(workspace_path, fetches, ignore_rust_version)
+    }
+
+    /// Names, base versions and `rust-version` of the dependencies to fetch
+    fn get_unique_dependencies(
+        &self,
+        workspace_path: Option<&str>,
+        ignore_rust_version: bool,
+    ) -> HashSet<FetchKey> {
+        let mut occurrences = Vec::new();
+        self.collect_occurrences(workspace_path, &mut occurrences);
+        get_unique_dependencies(&occurrences, ignore_rust_version)
+    }
+
+    /// Builds the outdated and up to date dependencies from the fetched responses, keyed by name,
+    /// base version and `rust-version`, reporting the failed fetches
+    fn into_dependencies(
+        self,
+        workspace_path: Option<String>,
+        fetches: HashMap<FetchKey, Fetched>,
+        ignore_rust_version: bool,
+    ) -> RetrievedDependencies {
+        let mut occurrences = Vec::new();
+        self.collect_occurrences(workspace_path.as_deref(), &mut occurrences);
+        let patched = self.patched_names();
+
+        let (mut dependencies, mut up_to_date_dependencies): (Vec<_>, Vec<_>) = occurrences
+            .iter()
+            .flat_map(|occurrence| {
+                let Ok((response, advisories)) =
+                    fetches.get(&occurrence.fetch_key(ignore_rust_version)?)?
+                else {
+                    return None;
+                };
+                let mut dependency = occurrence.dependency.to_dependency(
+                    response,
+                    advisories,
+                    occurrence.package_name,
+                    occurrence.workspace_path,
+                    occurrence.rust_version,
+                )?;
+                dependency.patched = patched.contains(&dependency.name);
+                Some(dependency)
+            })
+            .partition(|dependency| {
+                is_outdated(&dependency.current_version, &dependency.latest_version)
             });
 
         dependencies.sort();
+        up_to_date_dependencies.sort();
 
-        Dependencies::new(dependencies, cargo_toml_files)
+        let (ahead, up_to_date_dependencies) =
+            up_to_date_dependencies.into_iter().partition(|dependency| {
+                is_ahead(&dependency.current_version, &dependency.latest_version)
+            });
+
+        let mut failed = fetches
+            .into_iter()
+            .filter_map(|((name, _, _), fetched)| Some((name, fetched.err()?)))
+            .collect::<Vec<_>>();
+        failed.sort();
+        failed.dedup_by(|a, b| a.0 == b.0);
+
+        let mut cargo_toml_files = HashMap::new();
+        self.collect_cargo_toml_files(workspace_path, &mut cargo_toml_files);
+
+        RetrievedDependencies {
+            outdated: Dependencies::new(dependencies, cargo_toml_files),
+            up_to_date: up_to_date_dependencies,
+            ahead,
+            unchecked: 0,
+            failed,
+        }
+    }
+
+    fn collect_occurrences<'a>(
+        &'a self,
+        workspace_path: Option<&'a str>,
+        occurrences: &mut Vec<DependencyOccurrence<'a>>,
+    ) {
+        occurrences.extend(
+            self.dependencies
+                .iter()
+                .map(|dependency| DependencyOccurrence {
+                    dependency,
+                    package_name: &self.package_name,
+                    workspace_path,
+                    rust_version: self.rust_version.as_ref(),
+                }),
+        );
+
+        for (member, dependencies) in self.workspace_members.iter() {
+            dependencies.collect_occurrences(Some(member), occurrences);
+        }
+    }
+
+    fn collect_cargo_toml_files(
+        self,
+        workspace_path: Option<String>,
+        cargo_toml_files: &mut HashMap<String, DocumentMut>,
+    ) {
+        cargo_toml_files.insert(
+            workspace_path.unwrap_or_else(|| ".".to_string()),
+            self.cargo_toml,
+        );
+
+        for (member, dependencies) in self.workspace_members {
+            dependencies.collect_cargo_toml_files(Some(member), cargo_toml_files);
+        }
+    }
+
+    /// Removes the dependencies with the given names, from the package and its workspace members
+    pub fn exclude(&mut self, names: &[String]) {
+        self.dependencies
+            .retain(|dependency| !names.contains(&dependency.name));
+
+        for dependencies in self.workspace_members.values_mut() {
+            dependencies.exclude(names);
+        }
+    }
+
+    /// Keeps only the dependencies with the given names, in the package and its workspace members
+    pub fn retain_names(&mut self, names: &[String]) {
+        self.dependencies
+            .retain(|dependency| names.contains(&dependency.name));
+
+        for dependencies in self.workspace_members.values_mut() {
+            dependencies.retain_names(names);
+        }
+    }
+
+    /// Keeps only the dependencies pinned to an exact version with `=`, in the package and its
+    /// workspace members
+    pub fn retain_pinned(&mut self) {
+        self.dependencies
+            .retain(|dependency| dependency.version.trim_start().starts_with('='));
+
+        for dependencies in self.workspace_members.values_mut() {
+            dependencies.retain_pinned();
+        }
+    }
+
+    /// Sorted names of the entries of the `[patch.crates-io]` table of the root, which come from a
+    /// git repository or a path, so their version is not checked against crates.io. The ones of
+    /// the workspace members are ignored, as with cargo
+    pub fn patched_names(&self) -> Vec<String> {
+        get_patched_names(&self.cargo_toml)
+    }
+
+    /// Removes the dependencies of the given kinds, from the package and its workspace members
+    pub fn exclude_kinds(&mut self, kinds: &[DependencyKind]) {
+        self.dependencies
+            .retain(|dependency| !kinds.contains(&dependency.kind));
+
+        for dependencies in self.workspace_members.values_mut() {
+            dependencies.exclude_kinds(kinds);
+        }
+    }
+
+    /// Sorted names of the dependencies of the package and its workspace members
+    pub fn names(&self) -> Vec<String> {
+        let mut occurrences = Vec::new();
+        self.collect_occurrences(None, &mut occurrences);
+        sorted_names(
+            occurrences
+                .iter()
+                .map(|occurrence| occurrence.dependency.name.clone()),
+        )
+    }
+
+    /// Sorted names of the dependencies declared without a version requirement, which are not
+    /// checked
+    pub fn names_without_version(&self) -> Vec<String> {
+        self.collect_names(|dependencies| &dependencies.without_version)
+    }
+
+    /// Sorted names of the dependencies from another registry than crates.io, which are not
+    /// checked
+    pub fn names_from_other_registry(&self) -> Vec<String> {
+        self.collect_names(|dependencies| &dependencies.from_other_registry)
+    }
+
+    fn collect_names(&self, names: fn(&Self) -> &Vec<String>) -> Vec<String> {
+        sorted_names(
+            names(self).iter().cloned().chain(
+                self.workspace_members
+                    .values()
+                    .flat_map(|dependencies| dependencies.collect_names(names)),
+            ),
+        )
+    }
+
+    /// Sorted names of the dependencies whose requirement has no single lowest version, e.g.
+    /// `>=1, <2` or `*`, which are not checked
+    pub fn names_with_unsupported_requirement(&self) -> Vec<String> {
+        let mut occurrences = Vec::new();
+        self.collect_occurrences(None, &mut occurrences);
+        sorted_names(
+            occurrences
+                .iter()
+                .filter(|occurrence| get_base_version(&occurrence.dependency.version).is_none())
+                .map(|occurrence| occurrence.dependency.name.clone()),
+        )
     }
 
     pub fn len(&self) -> usize {
@@ -124,16 +559,269 @@ impl CargoDependencies {
     }
 }
 
-fn read_cargo_file(relative_path: &str) -> DocumentMut {
-    let cargo_toml_content = std::fs::read_to_string(format!("{relative_path}/Cargo.toml"))
-        .unwrap_or_else(|e| {
-            eprintln!("Unable to read Cargo.toml file: {}", e);
-            String::new()
+fn sorted_names(names: impl Iterator<Item = String>) -> Vec<String> {
+    let mut names = names.collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Whether the current version is newer than the latest one, e.g. when the latest version was
+/// yanked or when the latest one compatible with the `rust-version` of the package is older
+fn is_ahead(current_version: &str, latest_version: &str) -> bool {
+    match (
+        Version::parse(current_version),
+        Version::parse(latest_version),
+    ) {
+        (Ok(current_version), Ok(latest_version)) => current_version > latest_version,
+        _ => false,
+    }
+}
+
+fn is_outdated(current_version: &str, latest_version: &str) -> bool {
+    match (
+        Version::parse(current_version),
+        Version::parse(latest_version),
+    ) {
+        (Ok(current_version), Ok(latest_version)) => current_version < latest_version,
+        _ => false,
+    }
+}
+
+/// Names, base versions and `rust-version` of the dependencies to fetch, the same crate at the
+/// same version being only fetched once for a given `rust-version`
+fn get_unique_dependencies(
+    occurrences: &[DependencyOccurrence],
+    ignore_rust_version: bool,
+) -> HashSet<FetchKey> {
+    occurrences
+        .iter()
+        .flat_map(|occurrence| occurrence.fetch_key(ignore_rust_version))
+        .collect()
+}
+
+/// Lowest version matching a version requirement with a single `=`, `^` or `~` comparator (or no
+/// operator), e.g. `1.2.0` for `^1.2`
+fn get_base_version(requirement: &str) -> Option<Version> {
+    let requirement = VersionReq::parse(requirement).ok()?;
+
+    let [comparator] = requirement.comparators.as_slice() else {
+        return None;
+    };
+
+    if !matches!(comparator.op, Op::Exact | Op::Caret | Op::Tilde) {
+        return None;
+    }
+
+    Some(Version {
+        major: comparator.major,
+        minor: comparator.minor.unwrap_or(0),
+        patch: comparator.patch.unwrap_or(0),
+        pre: comparator.pre.clone(),
+        build: BuildMetadata::EMPTY,
+    })
+}
+
+/// Reason a crate could not be checked when looking up its security advisories failed, rather
+/// than reporting it without any
+fn format_advisories_error(e: &dyn std::error::Error) -> String {
+    format!("unable to check the security advisories: {e}")
+}
+
+/// Fetches the latest version of each crate along with, with `check_advisories`, the advisories
+/// affecting its current version, on a pool of at most [`MAX_CONCURRENT_FETCHES`] threads.
+/// Returns the fetch of each crate and the number of crates not fetched before the deadline
+fn fetch_latest_versions(
+    registry: Arc<dyn api::Registry>,
+    crates: HashSet<FetchKey>,
+    check_advisories: bool,
+    date_field: DateField,
+    deadline: Option<Instant>,
+) -> (HashMap<FetchKey, Fetched>, usize) {
+    let queue = Arc::new(Mutex::new(crates.iter().cloned().collect::<Vec<_>>()));
+    let (sender, receiver) = mpsc::channel();
+
+    for _ in 0..crates.len().min(MAX_CONCURRENT_FETCHES) {
+        let registry = Arc::clone(&registry);
+        let queue = Arc::clone(&queue);
+        let sender = sender.clone();
+        std::thread::spawn(move || loop {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+            let Some(key) = queue.lock().ok().and_then(|mut queue| queue.pop()) else {
+                break;
+            };
+
+            let (name, version, rust_version) = &key;
+            let fetched = api::get_latest_version(
+                registry.as_ref(),
+                name,
+                version,
+                rust_version.as_ref(),
+                date_field,
+            )
+            .map_err(|e| e.to_string())
+            .and_then(|response| {
+                // Advisories are only relevant for dependencies that can be updated
+                let advisories =
+                    if check_advisories && is_outdated(version, &response.latest_version) {
+                        api::get_advisories(registry.as_ref(), name, version)
+                            .map_err(|e| format_advisories_error(&*e))?
+                    } else {
+                        vec![]
+                    };
+
+                Ok((response, advisories))
+            });
+
+            // The receiver is gone once the deadline is exceeded
+            if sender.send((key, fetched)).is_err() {
+                break;
+            }
+        });
+    }
+    drop(sender);
+
+    let mut fetches = HashMap::new();
+    let mut timed_out = false;
+
+    while fetches.len() < crates.len() {
+        let received = match deadline {
+            Some(deadline) => {
+                receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            }
+            None => receiver.recv().map_err(RecvTimeoutError::from),
+        };
+
+        match received {
+            Ok((key, fetched)) => {
+                fetches.insert(key, fetched);
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                timed_out = true;
+                break;
+            }
+            // Every fetch thread is done, some of them having panicked or stopped at the deadline
+            Err(RecvTimeoutError::Disconnected) => {
+                timed_out = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+                break;
+            }
+        }
+    }
+
+    let missing = crates
+        .into_iter()
+        .filter(|key| !fetches.contains_key(key))
+        .collect::<Vec<_>>();
+    let unchecked = if timed_out { missing.len() } else { 0 };
+
+    if !timed_out {
+        for key in missing {
+            fetches.insert(key, Err("unexpected error while fetching".to_string()));
+        }
+    }
+
+    (fetches, unchecked)
+}
+
+/// Name of the source replacing crates.io in the Cargo config files, e.g. a mirror or vendored
+/// sources with `[source.crates-io] replace-with = "vendored-sources"`. The config files closest
+/// to the package take precedence, as with cargo, the one of the Cargo home directory coming last
+pub fn get_crates_io_replacement(manifest_dir: &Path) -> Option<String> {
+    let manifest_dir = manifest_dir
+        .canonicalize()
+        .unwrap_or_else(|_| manifest_dir.to_path_buf());
+    let cargo_home = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .or_else(|| std::env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".cargo"))
         });
 
+    manifest_dir
+        .ancestors()
+        .map(|dir| dir.join(".cargo"))
+        .chain(cargo_home)
+        .flat_map(|dir| [dir.join("config.toml"), dir.join("config")])
+        .filter_map(|path| {
+            std::fs::read_to_string(path)
+                .ok()?
+                .parse::<DocumentMut>()
+                .ok()
+        })
+        .find_map(|config| get_replace_with(&config))
+}
+
+fn get_replace_with(cargo_config: &DocumentMut) -> Option<String> {
+    Some(
+        cargo_config
+            .get("source")?
+            .get("crates-io")?
+            .get("replace-with")?
+            .as_str()?
+            .to_string(),
+    )
+}
+
+/// Cargo.lock file of the package, which is at the root of its workspace if it is part of one
+fn read_cargo_lock_file(manifest_dir: &Path) -> Option<DocumentMut> {
+    let manifest_dir = manifest_dir
+        .canonicalize()
+        .unwrap_or_else(|_| manifest_dir.to_path_buf());
+
+    manifest_dir
+        .ancestors()
+        .find_map(|dir| std::fs::read_to_string(dir.join("Cargo.lock")).ok())?
+        .parse()
+        .ok()
+}
+
+/// Names and versions of the crates.io packages of the Cargo.lock file of the package, empty when
+/// there is none
+pub fn get_locked_packages(manifest_dir: &Path) -> Vec<(String, String)> {
+    read_cargo_lock_file(manifest_dir)
+        .map(|cargo_lock| get_crates_io_packages(&cargo_lock))
+        .unwrap_or_default()
+}
+
+/// Names and versions of the packages of a Cargo.lock file coming from crates.io
+fn get_crates_io_packages(cargo_lock: &DocumentMut) -> Vec<(String, String)> {
+    const CRATES_IO_SOURCES: [&str; 2] = [
+        "registry+https://github.com/rust-lang/crates.io-index",
+        "sparse+https://index.crates.io/",
+    ];
+
+    let Some(packages) = cargo_lock.get("package").and_then(Item::as_array_of_tables) else {
+        return vec![];
+    };
+
+    packages
+        .iter()
+        .filter(|package| {
+            package
+                .get("source")
+                .and_then(Item::as_str)
+                .is_some_and(|source| CRATES_IO_SOURCES.contains(&source))
+        })
+        .flat_map(|package| {
+            Some((
+                package.get("name")?.as_str()?.to_string(),
+                package.get("version")?.as_str()?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+fn read_cargo_file(manifest_dir: &Path) -> Result<DocumentMut, Error> {
+    let path = manifest_dir.join("Cargo.toml");
+    let cargo_toml_content = std::fs::read_to_string(&path)
+        .map_err(|e| Error::Manifest(format!("unable to read {}: {e}", path.display())))?;
+
     cargo_toml_content
         .parse()
-        .expect("Unable to parse Cargo.toml file as TOML")
+        .map_err(|e| Error::Manifest(format!("unable to parse {} as TOML: {e}", path.display())))
 }
 
 fn get_cargo_dependencies(cargo_toml: &DocumentMut) -> Vec<CargoDependency> {
@@ -163,6 +851,71 @@ fn get_cargo_dependencies(cargo_toml: &DocumentMut) -> Vec<CargoDependency> {
         .collect()
 }
 
+/// Sorted names of the entries of the `[patch.crates-io]` table, whether they declare a version
+/// or not
+fn get_patched_names(cargo_toml: &DocumentMut) -> Vec<String> {
+    let Some(patches) = cargo_toml
+        .get("patch")
+        .and_then(|p| p.get("crates-io"))
+        .and_then(Item::as_table_like)
+    else {
+        return vec![];
+    };
+
+    sorted_names(patches.iter().map(|(name, _)| name.to_string()))
+}
+
+/// Names of the dependencies declared without a version requirement, e.g. path or git ones, which
+/// can't be checked against crates.io. The ones inheriting from the workspace are left out, their
+/// version being declared in the root, and so are the `[patch]` entries, reported on their own
+fn get_dependencies_without_version(cargo_toml: &DocumentMut) -> Vec<String> {
+    find_dependencies(cargo_toml, |package_data| {
+        !package_data.is_str()
+            && package_data.get("version").is_none()
+            && package_data.get("workspace").is_none()
+            && !is_from_other_registry(package_data)
+    })
+}
+
+/// Names of the dependencies from another registry than crates.io, which can't be checked
+/// against it
+fn get_dependencies_from_other_registries(cargo_toml: &DocumentMut) -> Vec<String> {
+    find_dependencies(cargo_toml, is_from_other_registry)
+}
+
+fn is_from_other_registry(package_data: &Item) -> bool {
+    package_data.get("registry").is_some() || package_data.get("registry-index").is_some()
+}
+
+/// Names of the dependencies of the package and of the workspace matching the predicate, the
+/// `[patch]` entries left out
+fn find_dependencies(cargo_toml: &DocumentMut, predicate: impl Fn(&Item) -> bool) -> Vec<String> {
+    let sections = [
+        cargo_toml.get("dependencies"),
+        cargo_toml.get("dev-dependencies"),
+        cargo_toml.get("build-dependencies"),
+        cargo_toml
+            .get("workspace")
+            .and_then(|w| w.get("dependencies")),
+    ];
+
+    sections
+        .into_iter()
+        .flatten()
+        .flat_map(|section| section.as_table_like())
+        .flat_map(|dependencies| {
+            dependencies
+                .iter()
+                .filter(|(_, package_data)| predicate(package_data))
+                .map(|(name, _)| name.to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Dependencies from crates.io with a version requirement. The ones inheriting from the workspace
+/// with `workspace = true`, whatever their other keys (`optional`, `features`...), are skipped as
+/// their version is declared, and updated, in the `[workspace.dependencies]` table of the root
 fn extract_dependencies_from_sections(
     dependencies_section: Option<&Item>,
     kind: DependencyKind,
@@ -178,6 +931,10 @@ fn extract_dependencies_from_sections(
     package_deps
         .iter()
         .flat_map(|(name, package_data)| {
+            if is_from_other_registry(package_data) {
+                return None;
+            }
+
             let version = match package_data {
                 Item::Value(Value::String(v)) => v.value().to_string(),
                 Item::Value(Value::InlineTable(t)) => t.get("version")?.as_str()?.to_string(),
@@ -194,28 +951,124 @@ fn extract_dependencies_from_sections(
         .collect()
 }
 
-fn get_workspace_members(cargo_toml: &DocumentMut) -> HashMap<String, Box<CargoDependencies>> {
+fn get_workspace_members(
+    cargo_toml: &DocumentMut,
+    manifest_dir: &Path,
+    depth: Option<usize>,
+) -> Result<HashMap<String, Box<CargoDependencies>>, Error> {
+    if depth == Some(0) {
+        return Ok(HashMap::new());
+    }
+
     let Some(workspace_members) = cargo_toml
         .get("workspace")
         .and_then(|i| i.get("members"))
         .and_then(|i| i.as_array())
     else {
-        return HashMap::new();
+        return Ok(HashMap::new());
     };
 
-    workspace_members
+    let excluded = cargo_toml
+        .get("workspace")
+        .and_then(|i| i.get("exclude"))
+        .and_then(|i| i.as_array())
+        .map(|exclude| {
+            exclude
+                .iter()
+                .flat_map(|path| path.as_str())
+                .map(normalize_member_path)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    // A member matched by a glob can also be listed explicitly, it is only gathered once. The
+    // excluded paths are directories, leaving out everything below them as well
+    let mut seen = HashSet::new();
+    let members = workspace_members
         .iter()
-        .fold(HashMap::new(), |mut acc, member| {
-            let Some(member) = member.as_str() else {
-                return acc;
-            };
+        .flat_map(|member| member.as_str())
+        .map(|member| expand_workspace_member(member, manifest_dir))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .filter(|member| {
+            let path = normalize_member_path(member);
+            !excluded
+                .iter()
+                .any(|excluded| Path::new(&path).starts_with(excluded))
+                && seen.insert(path)
+        })
+        .collect::<Vec<_>>();
 
-            acc.insert(
-                member.to_string(),
-                Box::new(CargoDependencies::gather_dependencies(member)),
-            );
-            acc
+    // Each member's Cargo.toml is independent, so they are read and parsed in parallel
+    std::thread::scope(|scope| {
+        members
+            .iter()
+            .map(|member| {
+                scope.spawn(move || -> Result<_, Error> {
+                    let dependencies = CargoDependencies::gather_dependencies(
+                        &manifest_dir.join(member),
+                        depth.map(|depth| depth - 1),
+                    )?;
+                    Ok((member.clone(), Box::new(dependencies)))
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect()
+    })
+}
+
+/// Paths of the members declared by an entry of `[workspace.members]`, relative to the workspace
+/// root. Glob patterns such as `crates/*` are expanded to the matching directories that contain a
+/// Cargo.toml file, as cargo does; other entries are kept as they are written. Fails on an invalid
+/// pattern, which cargo rejects as well
+fn expand_workspace_member(member: &str, manifest_dir: &Path) -> Result<Vec<String>, Error> {
+    if !member.contains(['*', '?', '[']) {
+        return Ok(vec![member.to_string()]);
+    }
+
+    let pattern = manifest_dir.join(member);
+    let paths = pattern
+        .to_str()
+        .ok_or_else(|| "the path is not valid UTF-8".to_string())
+        .and_then(|pattern| glob::glob(pattern).map_err(|e| e.to_string()))
+        .map_err(|e| {
+            Error::Manifest(format!("invalid workspace member pattern `{member}`: {e}"))
+        })?;
+
+    // The matches start with the workspace root, give or take the `.` components
+    let root_len = manifest_dir
+        .components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .count();
+
+    Ok(paths
+        .flatten()
+        .filter(|path| path.join("Cargo.toml").is_file())
+        .flat_map(|path| {
+            let components = path
+                .components()
+                .filter(|component| !matches!(component, Component::CurDir))
+                .skip(root_len)
+                .map(|component| component.as_os_str().to_str())
+                .collect::<Option<Vec<_>>>()?;
+            Some(components.join("/"))
         })
+        .collect())
+}
+
+/// Path of a member, or of an excluded directory, as it is compared with the others: without the
+/// leading `./` or the trailing `/`
+fn normalize_member_path(path: &str) -> String {
+    path.trim_start_matches("./")
+        .trim_end_matches('/')
+        .to_string()
 }
 
 fn get_package_name(cargo_toml: &DocumentMut) -> String {
@@ -227,6 +1080,20 @@ fn get_package_name(cargo_toml: &DocumentMut) -> String {
         .to_string()
 }
 
+/// `rust-version` of the package, falling back to the one shared with the workspace members, as
+/// in a virtual manifest or with `rust-version.workspace = true`
+fn get_rust_version(cargo_toml: &DocumentMut) -> Option<Version> {
+    let rust_version = |table: Option<&Item>| {
+        table?
+            .get("rust-version")?
+            .as_str()
+            .and_then(api::parse_rust_version)
+    };
+
+    rust_version(cargo_toml.get("package"))
+        .or_else(|| rust_version(cargo_toml.get("workspace").and_then(|w| w.get("package"))))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -248,22 +1115,744 @@ mod tests {
     }
 
     #[test]
-    fn test_get_cargo_dependencies() {
-        const CARGO_TOML: &str = r#"
-        [dependencies]
-        "dependencies" = "0.1.0"
-
-        [dev-dependencies]
-        "dev-dependencies" = "1.0.0"
-
-        [build-dependencies]
-        "build-dependencies" = "2.0.0"
+    fn test_cargo_dependencies_exclude() {
+        let dependency = |name: &str| CargoDependency {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let mut cargo_dependencies = CargoDependencies {
+            dependencies: vec![dependency("serde"), dependency("toml")],
+            workspace_members: HashMap::from_iter([(
+                "member".to_string(),
+                Box::new(CargoDependencies {
+                    dependencies: vec![dependency("serde"), dependency("semver")],
+                    ..Default::default()
+                }),
+            )]),
+            ..Default::default()
+        };
 
-        [workspace.dependencies]
-        "workspace-dependencies" = "3.0.0"
-        "#;
+        cargo_dependencies.exclude(&["serde".to_string()]);
+        assert_eq!(cargo_dependencies.len(), 2);
+        assert_eq!(cargo_dependencies.dependencies[0].name, "toml");
+    }
 
-        let cargo_toml: DocumentMut = CARGO_TOML.parse().unwrap();
+    #[test]
+    fn test_cargo_dependencies_retain_names() {
+        let dependency = |name: &str| CargoDependency {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let mut cargo_dependencies = CargoDependencies {
+            dependencies: vec![dependency("serde"), dependency("toml")],
+            workspace_members: HashMap::from_iter([(
+                "member".to_string(),
+                Box::new(CargoDependencies {
+                    dependencies: vec![dependency("serde"), dependency("semver")],
+                    ..Default::default()
+                }),
+            )]),
+            ..Default::default()
+        };
+
+        cargo_dependencies.retain_names(&["serde".to_string()]);
+        assert_eq!(cargo_dependencies.len(), 2);
+        assert_eq!(cargo_dependencies.dependencies[0].name, "serde");
+        assert_eq!(
+            cargo_dependencies.workspace_members["member"].dependencies[0].name,
+            "serde"
+        );
+    }
+
+    #[test]
+    fn test_cargo_dependencies_retain_pinned() {
+        let dependency = |name: &str, version: &str| CargoDependency {
+            name: name.to_string(),
+            version: version.to_string(),
+            ..Default::default()
+        };
+        let mut cargo_dependencies = CargoDependencies {
+            dependencies: vec![dependency("serde", "=1.0.100"), dependency("toml", "0.8")],
+            workspace_members: HashMap::from_iter([(
+                "member".to_string(),
+                Box::new(CargoDependencies {
+                    dependencies: vec![dependency("semver", "^1"), dependency("log", " = 0.4.20")],
+                    ..Default::default()
+                }),
+            )]),
+            ..Default::default()
+        };
+
+        cargo_dependencies.retain_pinned();
+        assert_eq!(cargo_dependencies.len(), 2);
+        assert_eq!(cargo_dependencies.dependencies[0].name, "serde");
+        assert_eq!(
+            cargo_dependencies.workspace_members["member"].dependencies[0].name,
+            "log"
+        );
+    }
+
+    #[test]
+    fn test_cargo_dependencies_exclude_kinds() {
+        let dependency = |name: &str, kind| CargoDependency {
+            name: name.to_string(),
+            kind,
+            ..Default::default()
+        };
+        let mut cargo_dependencies = CargoDependencies {
+            dependencies: vec![
+                dependency("serde", DependencyKind::Normal),
+                dependency("insta", DependencyKind::Dev),
+                dependency("cc", DependencyKind::Build),
+                dependency("toml", DependencyKind::Workspace),
+            ],
+            workspace_members: HashMap::from_iter([(
+                "member".to_string(),
+                Box::new(CargoDependencies {
+                    dependencies: vec![
+                        dependency("semver", DependencyKind::Normal),
+                        dependency("insta", DependencyKind::Dev),
+                    ],
+                    ..Default::default()
+                }),
+            )]),
+            ..Default::default()
+        };
+
+        cargo_dependencies.exclude_kinds(&[]);
+        assert_eq!(cargo_dependencies.len(), 6);
+
+        cargo_dependencies.exclude_kinds(&[DependencyKind::Dev]);
+        assert_eq!(cargo_dependencies.len(), 4);
+
+        cargo_dependencies.exclude_kinds(&[DependencyKind::Build, DependencyKind::Workspace]);
+        assert_eq!(cargo_dependencies.len(), 2);
+        assert_eq!(cargo_dependencies.dependencies[0].name, "serde");
+    }
+
+    #[test]
+    fn test_get_unique_dependencies() {
+        let dependency = |name: &str, version: &str, kind| CargoDependency {
+            name: name.to_string(),
+            version: version.to_string(),
+            kind,
+        };
+        let cargo_dependencies = CargoDependencies {
+            dependencies: vec![
+                dependency("serde", "1.0.0", DependencyKind::Normal),
+                dependency("toml", "0.8.0", DependencyKind::Dev),
+                dependency("invalid", ">1.0", DependencyKind::Normal),
+            ],
+            workspace_members: HashMap::from_iter([(
+                "member".to_string(),
+                Box::new(CargoDependencies {
+                    dependencies: vec![
+                        dependency("serde", "1.0.0", DependencyKind::Build),
+                        dependency("toml", "0.7.0", DependencyKind::Normal),
+                        dependency("toml", "=0.7", DependencyKind::Build),
+                    ],
+                    ..Default::default()
+                }),
+            )]),
+            ..Default::default()
+        };
+
+        let mut occurrences = Vec::new();
+        cargo_dependencies.collect_occurrences(None, &mut occurrences);
+        assert_eq!(occurrences.len(), 6);
+
+        let unique_dependencies = get_unique_dependencies(&occurrences, false);
+        assert_eq!(unique_dependencies.len(), 3);
+        assert!(unique_dependencies.contains(&("serde".to_string(), "1.0.0".to_string(), None)));
+        assert!(unique_dependencies.contains(&("toml".to_string(), "0.8.0".to_string(), None)));
+        assert!(unique_dependencies.contains(&("toml".to_string(), "0.7.0".to_string(), None)));
+    }
+
+    #[test]
+    fn test_get_unique_dependencies_by_rust_version() {
+        let serde = || CargoDependency {
+            name: "serde".to_string(),
+            version: "1.0.0".to_string(),
+            kind: DependencyKind::Normal,
+        };
+        let member = |rust_version| {
+            Box::new(CargoDependencies {
+                rust_version,
+                dependencies: vec![serde()],
+                ..Default::default()
+            })
+        };
+        let mut cargo_dependencies = CargoDependencies {
+            dependencies: vec![serde()],
+            workspace_members: HashMap::from_iter([
+                ("old".to_string(), member(Some(Version::new(1, 60, 0)))),
+                ("inheriting".to_string(), member(None)),
+            ]),
+            ..Default::default()
+        };
+        let rust_version = Version::new(1, 70, 0);
+        cargo_dependencies.inherit_rust_version(Some(&rust_version));
+
+        // A member with its own `rust-version` gets the versions compatible with it
+        let key = |rust_version| ("serde".to_string(), "1.0.0".to_string(), rust_version);
+        let mut occurrences = Vec::new();
+        cargo_dependencies.collect_occurrences(None, &mut occurrences);
+        let unique_dependencies = get_unique_dependencies(&occurrences, false);
+        assert_eq!(unique_dependencies.len(), 2);
+        assert!(unique_dependencies.contains(&key(Some(Version::new(1, 70, 0)))));
+        assert!(unique_dependencies.contains(&key(Some(Version::new(1, 60, 0)))));
+
+        let unique_dependencies = get_unique_dependencies(&occurrences, true);
+        assert_eq!(unique_dependencies, HashSet::from_iter([key(None)]));
+    }
+
+    #[test]
+    fn test_is_ahead() {
+        assert!(is_ahead("1.2.0", "1.1.0"));
+        assert!(!is_ahead("1.1.0", "1.1.0"));
+        assert!(!is_ahead("1.0.0", "1.1.0"));
+        assert!(!is_ahead("1.2", "1.1.0"));
+    }
+
+    #[test]
+    fn test_is_outdated() {
+        assert!(is_outdated("1.0.0", "1.1.0"));
+        assert!(!is_outdated("1.1.0", "1.1.0"));
+        assert!(!is_outdated("1.0.0", "latest"));
+    }
+
+    #[test]
+    fn test_to_dependency_latest_rust_version() {
+        let dependency = CargoDependency {
+            name: "serde".to_string(),
+            version: "1.0.0".to_string(),
+            kind: DependencyKind::Normal,
+        };
+        let response = |latest_rust_version: Option<&str>| api::CratesIoResponse {
+            repository: None,
+            description: None,
+            latest_version: "1.1.0".to_string(),
+            latest_version_date: None,
+            current_version_date: None,
+            downloads: None,
+            recent_downloads: None,
+            intermediate_versions: vec![],
+            yanked_versions: vec![],
+            latest_rust_version: latest_rust_version.map(str::to_string),
+            current_version_yanked: false,
+        };
+        let latest_rust_version = |latest_rust_version, rust_version| {
+            dependency
+                .to_dependency(&response(latest_rust_version), &[], "", None, rust_version)
+                .unwrap()
+                .latest_rust_version
+        };
+
+        let rust_version = Version::new(1, 70, 0);
+        assert_eq!(
+            latest_rust_version(Some("1.75"), Some(&rust_version)),
+            Some("1.75".to_string())
+        );
+        assert_eq!(latest_rust_version(Some("1.70"), Some(&rust_version)), None);
+        assert_eq!(
+            latest_rust_version(Some("1.56.1"), Some(&rust_version)),
+            None
+        );
+        assert_eq!(latest_rust_version(Some("1.75"), None), None);
+        assert_eq!(latest_rust_version(None, Some(&rust_version)), None);
+    }
+
+    #[test]
+    fn test_into_dependencies_reports_dependencies_ahead() {
+        let dependency = |name: &str, version: &str| CargoDependency {
+            name: name.to_string(),
+            version: version.to_string(),
+            kind: DependencyKind::Normal,
+        };
+        let cargo_dependencies = CargoDependencies {
+            dependencies: vec![
+                dependency("serde", "1.0.0"),
+                dependency("toml", "0.9.0"),
+                dependency("tokio", "1.0.0"),
+            ],
+            ..Default::default()
+        };
+        let response = |latest_version: &str| {
+            Ok((
+                api::CratesIoResponse {
+                    repository: None,
+                    description: None,
+                    latest_version: latest_version.to_string(),
+                    latest_version_date: None,
+                    current_version_date: None,
+                    downloads: None,
+                    recent_downloads: None,
+                    intermediate_versions: vec![],
+                    yanked_versions: vec![],
+                    latest_rust_version: None,
+                    current_version_yanked: false,
+                },
+                vec![],
+            ))
+        };
+
+        let retrieved = cargo_dependencies.into_dependencies(
+            None,
+            HashMap::from_iter([
+                (
+                    ("serde".to_string(), "1.0.0".to_string(), None),
+                    response("1.0.1"),
+                ),
+                (
+                    ("toml".to_string(), "0.9.0".to_string(), None),
+                    response("0.8.0"),
+                ),
+                (
+                    ("tokio".to_string(), "1.0.0".to_string(), None),
+                    response("1.0.0"),
+                ),
+            ]),
+            false,
+        );
+
+        let names = |dependencies: &[Dependency]| {
+            dependencies
+                .iter()
+                .map(|d| d.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&retrieved.outdated.dependencies), vec!["serde"]);
+        assert_eq!(names(&retrieved.up_to_date), vec!["tokio"]);
+        assert_eq!(names(&retrieved.ahead), vec!["toml"]);
+    }
+
+    #[test]
+    fn test_into_dependencies_marks_patched() {
+        let dependency = |name: &str| CargoDependency {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            kind: DependencyKind::Normal,
+        };
+        let cargo_dependencies = CargoDependencies {
+            cargo_toml: "[patch.crates-io]\nserde = { path = \"../serde\" }"
+                .parse()
+                .unwrap(),
+            dependencies: vec![dependency("serde")],
+            workspace_members: HashMap::from_iter([(
+                "member".to_string(),
+                Box::new(CargoDependencies {
+                    // Ignored by cargo outside of the root
+                    cargo_toml: "[patch.crates-io]\ntoml = { path = \"../toml\" }"
+                        .parse()
+                        .unwrap(),
+                    dependencies: vec![dependency("serde"), dependency("toml")],
+                    ..Default::default()
+                }),
+            )]),
+            ..Default::default()
+        };
+        let response = || {
+            Ok((
+                api::CratesIoResponse {
+                    repository: None,
+                    description: None,
+                    latest_version: "1.1.0".to_string(),
+                    latest_version_date: None,
+                    current_version_date: None,
+                    downloads: None,
+                    recent_downloads: None,
+                    intermediate_versions: vec![],
+                    yanked_versions: vec![],
+                    latest_rust_version: None,
+                    current_version_yanked: false,
+                },
+                vec![],
+            ))
+        };
+
+        let retrieved = cargo_dependencies.into_dependencies(
+            None,
+            HashMap::from_iter([
+                (("serde".to_string(), "1.0.0".to_string(), None), response()),
+                (("toml".to_string(), "1.0.0".to_string(), None), response()),
+            ]),
+            false,
+        );
+
+        let patched = retrieved
+            .outdated
+            .dependencies
+            .iter()
+            .map(|d| (d.name.as_str(), d.workspace_path.as_deref(), d.patched))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            patched,
+            [
+                ("serde", None, true),
+                ("serde", Some("member"), true),
+                ("toml", Some("member"), false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_into_dependencies_reports_failed_fetches() {
+        let dependency = |name: &str, version: &str| CargoDependency {
+            name: name.to_string(),
+            version: version.to_string(),
+            kind: DependencyKind::Normal,
+        };
+        let cargo_dependencies = CargoDependencies {
+            dependencies: vec![dependency("serde", "1.0.0"), dependency("toml", "0.8.0")],
+            ..Default::default()
+        };
+
+        let retrieved = cargo_dependencies.into_dependencies(
+            None,
+            HashMap::from_iter([
+                (
+                    ("toml".to_string(), "0.8.0".to_string(), None),
+                    Err("timed out".to_string()),
+                ),
+                (
+                    ("serde".to_string(), "1.0.0".to_string(), None),
+                    Err("not found".to_string()),
+                ),
+            ]),
+            false,
+        );
+
+        assert!(retrieved.outdated.is_empty());
+        assert!(retrieved.up_to_date.is_empty());
+        assert_eq!(retrieved.unchecked, 0);
+        assert_eq!(
+            retrieved.failed,
+            vec![
+                ("serde".to_string(), "not found".to_string()),
+                ("toml".to_string(), "timed out".to_string())
+            ]
+        );
+    }
+
+    /// Registry answering with canned crates.io responses, and an advisory for `serde 1.0.100`,
+    /// failing to look up the advisories of `unreachable`
+    struct MockRegistry(HashMap<&'static str, serde_json::Value>);
+
+    impl api::Registry for MockRegistry {
+        fn fetch_crate(&self, name: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+            let body = self.0.get(name).cloned().unwrap_or_else(
+                || serde_json::json!({ "errors": [{ "detail": "crate does not exist" }] }),
+            );
+            Ok(body.to_string().into_bytes())
+        }
+
+        fn fetch_advisories(
+            &self,
+            name: &str,
+            version: &str,
+        ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+            if name == "unreachable" {
+                return Err("connection refused".into());
+            }
+
+            let body = if (name, version) == ("serde", "1.0.100") {
+                serde_json::json!({ "vulns": [{ "id": "RUSTSEC-2024-0001" }] })
+            } else {
+                serde_json::json!({})
+            };
+            Ok(body.to_string().into_bytes())
+        }
+    }
+
+    #[test]
+    fn test_retrieve_outdated_dependencies_from_registry() {
+        let dependency = |name: &str, version: &str| CargoDependency {
+            name: name.to_string(),
+            version: version.to_string(),
+            kind: DependencyKind::Normal,
+        };
+        let cargo_dependencies = CargoDependencies {
+            dependencies: vec![
+                dependency("serde", "1.0.100"),
+                dependency("toml", "0.8"),
+                dependency("missing", "1.0.0"),
+            ],
+            ..Default::default()
+        };
+        let registry = MockRegistry(HashMap::from_iter([
+            (
+                "serde",
+                serde_json::json!({
+                    "crate": { "max_stable_version": "1.0.210" },
+                    "versions": [
+                        { "num": "1.0.210" },
+                        { "num": "1.0.200", "yanked": true },
+                        { "num": "1.0.150" },
+                        { "num": "1.0.100" }
+                    ]
+                }),
+            ),
+            (
+                "toml",
+                serde_json::json!({
+                    "crate": { "max_stable_version": "0.8.0" },
+                    "versions": [{ "num": "0.9.0-alpha.1" }, { "num": "0.8.0" }]
+                }),
+            ),
+        ]));
+
+        let retrieved = cargo_dependencies.retrieve_outdated_dependencies_from(
+            Arc::new(registry),
+            None,
+            false,
+            true,
+            DateField::Updated,
+            None,
+        );
+
+        let [serde] = retrieved.outdated.dependencies.as_slice() else {
+            panic!("only serde should be outdated");
+        };
+        assert_eq!(serde.latest_version, "1.0.210");
+        assert_eq!(serde.intermediate_versions, ["1.0.150"]);
+        assert_eq!(serde.yanked_versions, ["1.0.200"]);
+        assert_eq!(serde.advisories, ["RUSTSEC-2024-0001"]);
+
+        let [toml] = retrieved.up_to_date.as_slice() else {
+            panic!("only toml should be up to date");
+        };
+        assert_eq!(toml.name, "toml");
+        assert_eq!(
+            retrieved.failed,
+            [("missing".to_string(), "crate does not exist".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_retrieve_outdated_dependencies_advisories() {
+        let cargo_dependencies = CargoDependencies {
+            dependencies: vec![CargoDependency {
+                name: "unreachable".to_string(),
+                version: "1.0.0".to_string(),
+                kind: DependencyKind::Normal,
+            }],
+            ..Default::default()
+        };
+        let registry = Arc::new(MockRegistry(HashMap::from_iter([(
+            "unreachable",
+            serde_json::json!({
+                "crate": { "max_stable_version": "1.1.0" },
+                "versions": [{ "num": "1.1.0" }, { "num": "1.0.0" }]
+            }),
+        )])));
+
+        // Advisories are only looked up when asked for
+        let retrieved = cargo_dependencies
+            .clone()
+            .retrieve_outdated_dependencies_from(
+                registry.clone(),
+                None,
+                false,
+                false,
+                DateField::Updated,
+                None,
+            );
+        let [dependency] = retrieved.outdated.dependencies.as_slice() else {
+            panic!("unreachable should be outdated");
+        };
+        assert!(dependency.advisories.is_empty());
+        assert!(retrieved.failed.is_empty());
+
+        // A failed lookup is reported rather than taken as no advisories
+        let retrieved = cargo_dependencies.retrieve_outdated_dependencies_from(
+            registry,
+            None,
+            false,
+            true,
+            DateField::Updated,
+            None,
+        );
+        assert!(retrieved.outdated.is_empty());
+        assert_eq!(
+            retrieved.failed,
+            [(
+                "unreachable".to_string(),
+                "unable to check the security advisories: connection refused".to_string()
+            )]
+        );
+    }
+
+    /// Registry answering that every crate is up to date, after a while, recording the highest
+    /// number of fetches in flight at the same time
+    #[derive(Default)]
+    struct SlowRegistry {
+        in_flight: std::sync::atomic::AtomicUsize,
+        max_in_flight: std::sync::atomic::AtomicUsize,
+    }
+
+    impl api::Registry for SlowRegistry {
+        fn fetch_crate(&self, _name: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+            use std::sync::atomic::Ordering;
+
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            let body = serde_json::json!({
+                "crate": { "max_stable_version": "1.0.0" },
+                "versions": [{ "num": "1.0.0" }]
+            });
+            Ok(body.to_string().into_bytes())
+        }
+
+        fn fetch_advisories(
+            &self,
+            _name: &str,
+            _version: &str,
+        ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+            Ok(b"{}".to_vec())
+        }
+    }
+
+    #[test]
+    fn test_fetch_latest_versions_bounded() {
+        let registry = Arc::new(SlowRegistry::default());
+        let crates = (0..MAX_CONCURRENT_FETCHES * 4)
+            .map(|i| (format!("crate-{i}"), "1.0.0".to_string(), None))
+            .collect::<HashSet<_>>();
+
+        let (fetches, unchecked) =
+            fetch_latest_versions(registry.clone(), crates, false, DateField::Updated, None);
+
+        assert_eq!(fetches.len(), MAX_CONCURRENT_FETCHES * 4);
+        assert!(fetches.values().all(Result::is_ok));
+        assert_eq!(unchecked, 0);
+        assert!(
+            registry
+                .max_in_flight
+                .load(std::sync::atomic::Ordering::SeqCst)
+                <= MAX_CONCURRENT_FETCHES
+        );
+    }
+
+    #[test]
+    fn test_fetch_latest_versions_past_deadline() {
+        let crates = (0..4)
+            .map(|i| (format!("crate-{i}"), "1.0.0".to_string(), None))
+            .collect::<HashSet<_>>();
+
+        let (fetches, unchecked) = fetch_latest_versions(
+            Arc::new(SlowRegistry::default()),
+            crates,
+            false,
+            DateField::Updated,
+            Some(Instant::now()),
+        );
+
+        assert!(fetches.is_empty());
+        assert_eq!(unchecked, 4);
+    }
+
+    #[test]
+    fn test_get_replace_with() {
+        const CARGO_CONFIG: &str = r#"
+        [source.crates-io]
+        replace-with = "vendored-sources"
+
+        [source.vendored-sources]
+        directory = "vendor"
+        "#;
+
+        assert_eq!(
+            get_replace_with(&CARGO_CONFIG.parse().unwrap()),
+            Some("vendored-sources".to_string())
+        );
+        assert_eq!(get_replace_with(&"[net]\nretry = 3".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn test_get_crates_io_packages() {
+        const CARGO_LOCK: &str = r#"
+        version = 4
+
+        [[package]]
+        name = "cargo-interactive-update"
+        version = "0.1.0"
+
+        [[package]]
+        name = "serde"
+        version = "1.0.0"
+        source = "registry+https://github.com/rust-lang/crates.io-index"
+
+        [[package]]
+        name = "toml"
+        version = "0.8.0"
+        source = "sparse+https://index.crates.io/"
+
+        [[package]]
+        name = "private"
+        version = "0.1.0"
+        source = "registry+https://my-registry.example.com/index"
+
+        [[package]]
+        name = "forked"
+        version = "0.1.0"
+        source = "git+https://github.com/user/forked#abcdef"
+        "#;
+
+        let cargo_lock = CARGO_LOCK.parse().unwrap();
+        assert_eq!(
+            get_crates_io_packages(&cargo_lock),
+            vec![
+                ("serde".to_string(), "1.0.0".to_string()),
+                ("toml".to_string(), "0.8.0".to_string())
+            ]
+        );
+        assert!(get_crates_io_packages(&DocumentMut::new()).is_empty());
+    }
+
+    #[test]
+    fn test_get_base_version() {
+        let base_version = |requirement| get_base_version(requirement).map(|v| v.to_string());
+
+        assert_eq!(base_version("1.2.3"), Some("1.2.3".to_string()));
+        assert_eq!(base_version("^1.2"), Some("1.2.0".to_string()));
+        assert_eq!(base_version("~1"), Some("1.0.0".to_string()));
+        assert_eq!(
+            base_version("=0.1.0-alpha.1"),
+            Some("0.1.0-alpha.1".to_string())
+        );
+        assert_eq!(base_version(">=1.2"), None);
+        assert_eq!(base_version(">=1.2, <2"), None);
+        assert_eq!(base_version("*"), None);
+        assert_eq!(base_version("not a version"), None);
+    }
+
+    #[test]
+    fn test_get_cargo_dependencies() {
+        const CARGO_TOML: &str = r#"
+        [dependencies]
+        "dependencies" = "0.1.0"
+
+        [dev-dependencies]
+        "dev-dependencies" = "1.0.0"
+
+        [build-dependencies]
+        "build-dependencies" = "2.0.0"
+
+        [workspace.dependencies]
+        "workspace-dependencies" = "3.0.0"
+
+        [patch.crates-io]
+        "patch-dependencies" = { git = "https://github.com/user/repo", version = "4.0.0" }
+        "unversioned-patch-dependencies" = { path = "../repo" }
+
+        [patch."https://github.com/user/other"]
+        "git-patch-dependencies" = { path = "../other", version = "5.0.0" }
+        "#;
+
+        let cargo_toml: DocumentMut = CARGO_TOML.parse().unwrap();
         let dependencies = get_cargo_dependencies(&cargo_toml);
         assert_eq!(dependencies.len(), 4);
         assert!(dependencies.contains(&CargoDependency {
@@ -288,6 +1877,25 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn test_get_patched_names() {
+        const CARGO_TOML: &str = r#"
+        [dependencies]
+        serde = "1.0.100"
+
+        [patch.crates-io]
+        toml = { path = "../toml" }
+        serde = { git = "https://github.com/user/serde", version = "1.0.100" }
+
+        [patch."https://github.com/user/other"]
+        other = { path = "../other" }
+        "#;
+
+        let cargo_toml: DocumentMut = CARGO_TOML.parse().unwrap();
+        assert_eq!(get_patched_names(&cargo_toml), ["serde", "toml"]);
+        assert!(get_patched_names(&DocumentMut::new()).is_empty());
+    }
+
     #[test]
     fn test_extract_dependencies_from_sections() {
         const CARGO_TOML: &str = r#"
@@ -329,6 +1937,113 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn test_extract_dependencies_inherited_from_workspace() {
+        const ROOT_CARGO_TOML: &str = r#"
+        [workspace.dependencies]
+        serde = { version = "1.0.0", default-features = false }
+        tokio = "1.40.0"
+        "#;
+
+        const MEMBER_CARGO_TOML: &str = r#"
+        [dependencies]
+        serde = { workspace = true, optional = true }
+        tokio = { workspace = true, features = ["rt"] }
+        toml = { version = "0.8.0", optional = true }
+
+        [dependencies.other]
+        workspace = true
+        optional = true
+        "#;
+
+        let cargo_toml: DocumentMut = ROOT_CARGO_TOML.parse().unwrap();
+        let dependencies = get_cargo_dependencies(&cargo_toml);
+        assert_eq!(dependencies.len(), 2);
+        assert!(dependencies.contains(&CargoDependency {
+            name: "serde".to_string(),
+            version: "1.0.0".to_string(),
+            kind: DependencyKind::Workspace
+        }));
+        assert!(dependencies.contains(&CargoDependency {
+            name: "tokio".to_string(),
+            version: "1.40.0".to_string(),
+            kind: DependencyKind::Workspace
+        }));
+
+        let cargo_toml: DocumentMut = MEMBER_CARGO_TOML.parse().unwrap();
+        let dependencies = get_cargo_dependencies(&cargo_toml);
+        assert_eq!(dependencies.len(), 1);
+        assert!(dependencies.contains(&CargoDependency {
+            name: "toml".to_string(),
+            version: "0.8.0".to_string(),
+            kind: DependencyKind::Normal
+        }));
+    }
+
+    #[test]
+    fn test_get_dependencies_without_version() {
+        const CARGO_TOML: &str = r#"
+        [dependencies]
+        serde = "1.0.100"
+        local = { path = "../local" }
+        inherited = { workspace = true }
+        internal = { version = "1.0.0", registry = "my-corp" }
+
+        [dev-dependencies.remote]
+        git = "https://github.com/user/remote"
+
+        [patch.crates-io]
+        serde = { path = "../serde" }
+        "#;
+
+        let cargo_toml = CARGO_TOML.parse().unwrap();
+        assert_eq!(
+            get_dependencies_without_version(&cargo_toml),
+            ["local", "remote"]
+        );
+        assert_eq!(
+            get_dependencies_from_other_registries(&cargo_toml),
+            ["internal"]
+        );
+        assert!(
+            extract_dependencies_from_sections(
+                cargo_toml.get("dependencies"),
+                DependencyKind::Normal
+            ) == [CargoDependency {
+                name: "serde".to_string(),
+                version: "1.0.100".to_string(),
+                kind: DependencyKind::Normal,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_names_with_unsupported_requirement() {
+        let cargo_dependencies = CargoDependencies {
+            dependencies: vec![
+                CargoDependency {
+                    name: "serde".to_string(),
+                    version: "1.0.100".to_string(),
+                    kind: DependencyKind::Normal,
+                },
+                CargoDependency {
+                    name: "toml".to_string(),
+                    version: ">=0.7, <0.9".to_string(),
+                    kind: DependencyKind::Normal,
+                },
+            ],
+            without_version: vec!["local".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(cargo_dependencies.names(), ["serde", "toml"]);
+        assert_eq!(
+            cargo_dependencies.names_with_unsupported_requirement(),
+            ["toml"]
+        );
+        assert_eq!(cargo_dependencies.names_without_version(), ["local"]);
+    }
+
     #[test]
     fn test_extract_dependencies_with_none_dependencies_section() {
         let dependencies = extract_dependencies_from_sections(None, DependencyKind::Normal);
@@ -348,14 +2063,156 @@ mod tests {
     fn test_get_workspace_members() {
         const CARGO_TOML: &str = r#"
         [workspace]
-        members = ["workspace-member-1", "workspace-member-2", 0]
+        members = ["first-package", "second-package", 0]
+        "#;
+
+        let manifest_dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/packages/multiple_packages");
+        let cargo_toml = CARGO_TOML.parse().unwrap();
+        let workspace_members = get_workspace_members(&cargo_toml, &manifest_dir, None).unwrap();
+        assert_eq!(workspace_members.len(), 2);
+        assert!(workspace_members.contains_key("first-package"));
+        assert!(workspace_members.contains_key("second-package"));
+
+        assert!(get_workspace_members(&cargo_toml, &manifest_dir, Some(0))
+            .unwrap()
+            .is_empty());
+        let workspace_members = get_workspace_members(&cargo_toml, &manifest_dir, Some(1)).unwrap();
+        assert_eq!(workspace_members.len(), 2);
+    }
+
+    #[test]
+    fn test_get_workspace_members_with_invalid_manifest() {
+        const CARGO_TOML: &str = r#"
+        [workspace]
+        members = ["missing-member"]
+        "#;
+
+        let manifest_dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/packages/multiple_packages");
+        let cargo_toml = CARGO_TOML.parse().unwrap();
+        let error = get_workspace_members(&cargo_toml, &manifest_dir, None).unwrap_err();
+        assert!(matches!(error, Error::Manifest(_)));
+        assert!(error.to_string().starts_with("unable to read "));
+    }
+
+    #[test]
+    fn test_read_cargo_file_with_invalid_toml() {
+        let dir = std::env::temp_dir().join(format!("invalid-toml-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Cargo.toml"), "[dependencies\nserde = ").unwrap();
+
+        let error = read_cargo_file(&dir).unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(error.to_string().starts_with("unable to parse "));
+    }
+
+    #[test]
+    fn test_get_workspace_members_with_glob() {
+        const CARGO_TOML: &str = r#"
+        [workspace]
+        members = ["crates/*"]
         "#;
 
+        let manifest_dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/packages/glob_members");
         let cargo_toml = CARGO_TOML.parse().unwrap();
-        let workspace_members = get_workspace_members(&cargo_toml);
+        let workspace_members = get_workspace_members(&cargo_toml, &manifest_dir, None).unwrap();
         assert_eq!(workspace_members.len(), 2);
-        assert!(workspace_members.contains_key("workspace-member-1"));
-        assert!(workspace_members.contains_key("workspace-member-2"));
+        assert_eq!(
+            workspace_members["crates/first-crate"].package_name,
+            "first-crate"
+        );
+        assert_eq!(
+            workspace_members["crates/second-crate"].package_name,
+            "second-crate"
+        );
+    }
+
+    #[test]
+    fn test_get_workspace_members_with_invalid_glob() {
+        const CARGO_TOML: &str = r#"
+        [workspace]
+        members = ["crates/[first-crate"]
+        "#;
+
+        let manifest_dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/packages/glob_members");
+        let cargo_toml = CARGO_TOML.parse().unwrap();
+        let error = get_workspace_members(&cargo_toml, &manifest_dir, None).unwrap_err();
+        assert!(matches!(error, Error::Manifest(_)));
+        assert!(error
+            .to_string()
+            .starts_with("invalid workspace member pattern `crates/[first-crate`: "));
+    }
+
+    #[test]
+    fn test_get_workspace_members_deduplicated_and_excluded() {
+        const CARGO_TOML: &str = r#"
+        [workspace]
+        members = ["crates/*", "./crates/first-crate/", "crates/second-crate"]
+        exclude = ["./crates/second-crate/"]
+        "#;
+
+        let manifest_dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/packages/glob_members");
+        let cargo_toml = CARGO_TOML.parse().unwrap();
+        let mut members = get_workspace_members(&cargo_toml, &manifest_dir, None)
+            .unwrap()
+            .into_keys()
+            .collect::<Vec<_>>();
+        members.sort();
+        assert_eq!(members, ["crates/first-crate"]);
+    }
+
+    #[test]
+    fn test_get_workspace_members_with_glob_and_exclude() {
+        let manifest_dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/packages/glob_members");
+
+        let cargo_toml = r#"
+        [workspace]
+        members = ["crates/*"]
+        exclude = ["crates/second-crate"]
+        "#
+        .parse()
+        .unwrap();
+        let workspace_members = get_workspace_members(&cargo_toml, &manifest_dir, None).unwrap();
+        assert_eq!(workspace_members.len(), 1);
+        assert!(workspace_members.contains_key("crates/first-crate"));
+
+        let cargo_toml = r#"
+        [workspace]
+        members = ["crates/*"]
+        exclude = ["crates"]
+        "#
+        .parse()
+        .unwrap();
+        let workspace_members = get_workspace_members(&cargo_toml, &manifest_dir, None).unwrap();
+        assert!(workspace_members.is_empty());
+    }
+
+    #[test]
+    fn test_normalize_member_path() {
+        assert_eq!(normalize_member_path("crates/foo"), "crates/foo");
+        assert_eq!(normalize_member_path("./crates/foo/"), "crates/foo");
+    }
+
+    #[test]
+    fn test_gather_dependencies_relative_to_manifest_dir() {
+        let manifest_dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/packages/multiple_packages");
+        let cargo_dependencies =
+            CargoDependencies::gather_dependencies(&manifest_dir, None).unwrap();
+        assert_eq!(cargo_dependencies.package_name, "multiple-packages");
+        assert_eq!(
+            cargo_dependencies.workspace_members["first-package"].package_name,
+            "first-package"
+        );
+        assert_eq!(
+            cargo_dependencies.workspace_members["second-package"].package_name,
+            "second-package"
+        );
     }
 
     #[test]
@@ -366,7 +2223,7 @@ mod tests {
         "#;
 
         let cargo_toml = CARGO_TOML.parse().unwrap();
-        let workspace_members = get_workspace_members(&cargo_toml);
+        let workspace_members = get_workspace_members(&cargo_toml, Path::new("."), None).unwrap();
         assert_eq!(workspace_members.len(), 0);
     }
 
@@ -382,6 +2239,57 @@ mod tests {
         assert_eq!(package_name, "");
     }
 
+    #[test]
+    fn test_get_rust_version() {
+        const CARGO_TOML: &str = r#"
+        [package]
+        name = "cargo-outdated"
+        rust-version = "1.70"
+        "#;
+
+        let cargo_toml = CARGO_TOML.parse().unwrap();
+        let rust_version = get_rust_version(&cargo_toml);
+        assert_eq!(rust_version, Some(Version::new(1, 70, 0)));
+    }
+
+    #[test]
+    fn test_get_rust_version_from_workspace() {
+        const CARGO_TOML: &str = r#"
+        [workspace]
+        members = ["member"]
+
+        [workspace.package]
+        rust-version = "1.65.0"
+        "#;
+
+        let cargo_toml = CARGO_TOML.parse().unwrap();
+        assert_eq!(get_rust_version(&cargo_toml), Some(Version::new(1, 65, 0)));
+
+        const INHERITED_CARGO_TOML: &str = r#"
+        [package]
+        name = "cargo-outdated"
+        rust-version.workspace = true
+
+        [workspace.package]
+        rust-version = "1.65"
+        "#;
+
+        let cargo_toml = INHERITED_CARGO_TOML.parse().unwrap();
+        assert_eq!(get_rust_version(&cargo_toml), Some(Version::new(1, 65, 0)));
+    }
+
+    #[test]
+    fn test_get_rust_version_with_no_rust_version() {
+        const CARGO_TOML: &str = r#"
+        [package]
+        name = "cargo-outdated"
+        "#;
+
+        let cargo_toml = CARGO_TOML.parse().unwrap();
+        let rust_version = get_rust_version(&cargo_toml);
+        assert_eq!(rust_version, None);
+    }
+
     #[test]
     fn test_get_package_name() {
         const CARGO_TOML: &str = r#"
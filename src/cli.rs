@@ -1,34 +1,130 @@
 use crossterm::{
-    cursor::{Hide, MoveTo, MoveToNextLine, Show},
-    event::{self, KeyCode, KeyModifiers},
+    cursor::{Hide, Show},
+    event::{self, KeyCode, KeyEvent, KeyModifiers},
     execute,
-    style::{Print, PrintStyledContent, ResetColor, Stylize},
-    terminal::{
-        disable_raw_mode, enable_raw_mode, Clear, ClearType, DisableLineWrap, EnableLineWrap,
-    },
+    style::{ResetColor, StyledContent, Stylize},
+    terminal::{disable_raw_mode, enable_raw_mode, size},
+};
+use std::{
+    collections::HashSet,
+    io::{stdout, IsTerminal, Write},
+    path::{Component, Path, PathBuf},
 };
-use std::io::{stdout, Write};
 
-use crate::dependency::{Dependencies, Dependency, DependencyKind};
+use crate::frame::Frame;
+use cargo_interactive_update::{
+    AppliedUpdate, ApplyOptions, ApplyReport, Dependencies, Dependency, DependencyKind, ScanResult,
+    SkippedDependencies, SortOrder, UpdateType, VersionOperator,
+};
 
 pub struct State {
     stdout: std::io::Stdout,
     selected: Vec<bool>,
     cursor_location: usize,
     outdated_deps: Dependencies,
+    up_to_date_deps: Vec<Dependency>,
+    transitive_deps: Vec<Dependency>,
     total_deps: usize,
+    unchecked_deps: usize,
+    failed_deps: Vec<(String, String)>,
     longest_attributes: Longest,
+    update_type_counts: UpdateTypeCounts,
+    terminal_width: usize,
+    terminal_height: usize,
+    show_downloads: bool,
+    show_releases_behind: bool,
+    sort_order: SortOrder,
+    update_type_filter: Option<UpdateType>,
+    /// Only show the selected dependencies, to review them before updating
+    show_selected_only: bool,
+    show_relative_dates: bool,
+    today: Option<i64>,
+    /// Name typed after `/` to jump to, while `jumping`
+    jump_buffer: String,
+    jumping: bool,
+    show_details: bool,
+    show_yanked: bool,
+    show_legend: bool,
+    /// Whether <enter> was pressed with selected major updates requiring a newer Rust, a second
+    /// <enter> updating anyway
+    confirming_update: bool,
+    /// Message shown in the footer until the next key is pressed
+    status: Option<String>,
+    /// Directory of the scanned Cargo.toml file, to locate the ones of the workspace members
+    manifest_dir: PathBuf,
+    /// Whether the manifest paths are shown as clickable links
+    hyperlinks: bool,
+    /// Lines drawn by the last render, to only rewrite the ones that changed
+    previous_frame: Option<Frame>,
+    terminal_guard: Option<TerminalGuard>,
+}
+
+/// Raw mode with a hidden cursor, restored when dropped whatever the exit path, an error
+/// propagated with `?` included. Panics restore it too, before their message is printed, as the
+/// release profile aborts without unwinding
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            hook(info);
+        }));
+
+        enable_raw_mode()?;
+        execute!(stdout(), Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+fn restore_terminal() {
+    let _ = execute!(stdout(), Show, ResetColor);
+    let _ = disable_raw_mode();
 }
 
 pub enum Event {
     HandleKeyboard,
     UpdateDependencies,
+    /// Check again the dependencies that could not be checked
+    RefreshFailed,
+    /// Check again all the dependencies
+    RefreshAll,
     Exit,
 }
 
+#[derive(Debug, Default, PartialEq, Eq)]
+struct UpdateTypeCounts {
+    patch: usize,
+    minor: usize,
+    major: usize,
+}
+
+impl UpdateTypeCounts {
+    fn count<'a>(dependencies: impl IntoIterator<Item = &'a Dependency>) -> Self {
+        dependencies
+            .into_iter()
+            .fold(Self::default(), |mut counts, dep| {
+                match dep.update_type() {
+                    Some(UpdateType::Patch) => counts.patch += 1,
+                    Some(UpdateType::Minor) => counts.minor += 1,
+                    Some(UpdateType::Major) => counts.major += 1,
+                    None => {}
+                }
+                counts
+            })
+    }
+}
+
 struct Longest {
     name: usize,
-    current_version: usize,
+    requirement: usize,
     latest_version: usize,
     package_name: usize,
 }
@@ -36,20 +132,20 @@ struct Longest {
 impl Longest {
     fn get_longest_attributes(dependencies: &Dependencies) -> Longest {
         let mut name = 0;
-        let mut current_version = 0;
+        let mut requirement = 0;
         let mut latest_version = 0;
         let mut package_name = 0;
 
         for dep in dependencies.iter() {
             name = name.max(dep.name.len());
-            current_version = current_version.max(dep.current_version.len());
+            requirement = requirement.max(dep.requirement.len());
             latest_version = latest_version.max(dep.latest_version.len());
             package_name = package_name.max(dep.package_name.as_ref().map_or(0, |s| s.len()));
         }
 
         Longest {
             name,
-            current_version,
+            requirement,
             latest_version,
             package_name,
         }
@@ -57,201 +153,940 @@ impl Longest {
 }
 
 impl State {
-    pub fn new(outdated_deps: Dependencies, total_deps: usize, default_selected: bool) -> Self {
+    pub fn new(
+        outdated_deps: Dependencies,
+        total_deps: usize,
+        default_selected: impl Fn(&Dependency) -> bool,
+        previously_selected: &HashSet<String>,
+        up_to_date_deps: Vec<Dependency>,
+        unchecked_deps: usize,
+        transitive_deps: Vec<Dependency>,
+    ) -> Self {
         Self {
             stdout: stdout(),
-            selected: vec![default_selected; outdated_deps.len()],
+            selected: outdated_deps
+                .iter()
+                .map(|dep| {
+                    default_selected(dep)
+                        || previously_selected.contains(&dep.name)
+                        || !dep.advisories.is_empty()
+                })
+                .collect(),
             cursor_location: 0,
             longest_attributes: Longest::get_longest_attributes(&outdated_deps),
+            update_type_counts: UpdateTypeCounts::count(outdated_deps.iter()),
             outdated_deps,
+            up_to_date_deps,
+            transitive_deps,
             total_deps,
+            unchecked_deps,
+            failed_deps: vec![],
+            terminal_width: 0,
+            terminal_height: 0,
+            show_downloads: false,
+            show_releases_behind: false,
+            sort_order: SortOrder::default(),
+            update_type_filter: None,
+            show_selected_only: false,
+            show_relative_dates: true,
+            today: get_days_since_epoch_today(),
+            jump_buffer: String::new(),
+            jumping: false,
+            show_details: false,
+            show_yanked: false,
+            show_legend: false,
+            confirming_update: false,
+            status: None,
+            manifest_dir: PathBuf::from("."),
+            hyperlinks: crate::hyperlink::is_supported(),
+            previous_frame: None,
+            terminal_guard: None,
         }
     }
 
+    /// Switches the terminal to raw mode until the state is dropped
     pub fn start(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        enable_raw_mode()?;
-        execute!(self.stdout, Hide)?;
+        self.terminal_guard = Some(TerminalGuard::new()?);
         Ok(())
     }
 
     pub fn handle_keyboard_event(&mut self) -> Result<Event, Box<dyn std::error::Error>> {
         if let event::Event::Key(key) = event::read()? {
+            return self.handle_key(key);
+        }
+
+        Ok(Event::HandleKeyboard)
+    }
+
+    /// Handles a key press, returning the event for the caller to act on
+    fn handle_key(&mut self, key: KeyEvent) -> Result<Event, Box<dyn std::error::Error>> {
+        self.status = None;
+
+        if self.show_details {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('v') => {
+                    self.show_details = false;
+                }
+                KeyCode::Char('y') => {
+                    self.show_yanked = !self.show_yanked;
+                }
+                _ => {}
+            }
+            return Ok(Event::HandleKeyboard);
+        }
+
+        // While a name is being typed, the keys bound to actions extend it instead. Any other
+        // key than <enter> and <esc>, which only end the jump, ends it and does as usual
+        if self.jumping {
             match (key.code, key.modifiers) {
-                (KeyCode::Up | KeyCode::Left, _) => {
-                    self.cursor_location = if self.cursor_location == 0 {
-                        self.outdated_deps.len() - 1
-                    } else {
-                        self.cursor_location - 1
-                    };
+                (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT)
+                    if is_crate_name_char(c) =>
+                {
+                    self.jump_buffer.push(c);
+                    self.jump();
+                    return Ok(Event::HandleKeyboard);
                 }
-                (KeyCode::Down | KeyCode::Right, _) => {
-                    self.cursor_location = (self.cursor_location + 1) % self.outdated_deps.len();
+                (KeyCode::Backspace, _) => {
+                    self.jump_buffer.pop();
+                    self.jump();
+                    return Ok(Event::HandleKeyboard);
                 }
-                (KeyCode::Char(' '), _) => {
-                    self.selected[self.cursor_location] = !self.selected[self.cursor_location];
+                (KeyCode::Enter | KeyCode::Esc, _) => {
+                    self.jumping = false;
+                    return Ok(Event::HandleKeyboard);
                 }
-                (KeyCode::Enter, _) => {
-                    self.reset_terminal()?;
-                    return Ok(Event::UpdateDependencies);
+                _ => {
+                    self.jumping = false;
+                }
+            }
+        }
+
+        // Any other key than <enter> goes back to the selection, and does as usual
+        if self.confirming_update {
+            self.confirming_update = false;
+            if key.code == KeyCode::Enter {
+                return Ok(Event::UpdateDependencies);
+            }
+        }
+
+        match (key.code, key.modifiers) {
+            (KeyCode::Char('/'), _) => {
+                self.jumping = true;
+                self.jump_buffer.clear();
+            }
+            (KeyCode::Up | KeyCode::Left, _) => {
+                self.move_cursor(|position, len| (position + len - 1) % len);
+            }
+            (KeyCode::Down | KeyCode::Right, _) => {
+                self.move_cursor(|position, len| (position + 1) % len);
+            }
+            (KeyCode::PageUp, _) => {
+                let page_size = self.page_size();
+                self.move_cursor(|position, _| position.saturating_sub(page_size));
+            }
+            (KeyCode::PageDown, _) => {
+                let page_size = self.page_size();
+                self.move_cursor(|position, _| position + page_size);
+            }
+            (KeyCode::Home, _) => {
+                self.move_cursor(|_, _| 0);
+            }
+            (KeyCode::End, _) => {
+                self.move_cursor(|_, len| len - 1);
+            }
+            (KeyCode::Char('n' | 'N'), _) => {
+                // Hidden dependencies are skipped as if they were selected
+                let skipped = (0..self.selected.len())
+                    .map(|i| self.selected[i] || !self.is_visible(i))
+                    .collect::<Vec<_>>();
+                let forward = key.code == KeyCode::Char('n');
+
+                if let Some(i) = find_unselected(&skipped, self.cursor_location, forward) {
+                    self.cursor_location = i;
                 }
-                (KeyCode::Char('a'), _) => {
-                    self.selected = vec![true; self.outdated_deps.len()];
+            }
+            (KeyCode::Char(' '), _) => {
+                self.selected[self.cursor_location] = !self.selected[self.cursor_location];
+                self.keep_cursor_visible();
+            }
+            (KeyCode::Enter, _) => {
+                let selected = self
+                    .outdated_deps
+                    .iter()
+                    .zip(&self.selected)
+                    .filter(|(_, selected)| **selected)
+                    .map(|(dependency, _)| dependency);
+
+                // The user can still go back on the updates they can't build with yet
+                let Some(warnings) = format_rust_version_warnings(selected) else {
+                    return Ok(Event::UpdateDependencies);
+                };
+                self.confirming_update = true;
+                self.status = Some(format!(
+                    "{warnings}\nPress {} to update anyway, any other key to go back",
+                    "<enter>".cyan()
+                ));
+            }
+            (KeyCode::Char('a'), _) => {
+                for i in self.visible_indices() {
+                    self.selected[i] = true;
                 }
-                (KeyCode::Char('i'), _) => {
-                    self.selected = self.selected.iter().map(|s| !s).collect();
+            }
+            (KeyCode::Char('A'), _) => {
+                for i in self.visible_indices() {
+                    self.selected[i] = false;
                 }
-                (KeyCode::Esc | KeyCode::Char('q'), _)
-                | (KeyCode::Char('c') | KeyCode::Char('z'), KeyModifiers::CONTROL) => {
-                    self.reset_terminal()?;
-                    return Ok(Event::Exit);
+                self.keep_cursor_visible();
+            }
+            (KeyCode::Char('i'), _) => {
+                for i in self.visible_indices() {
+                    self.selected[i] = !self.selected[i];
                 }
-                _ => {}
+                self.keep_cursor_visible();
+            }
+            (KeyCode::Tab, _) => {
+                self.toggle_selected_only();
+            }
+            (KeyCode::Char('f'), _) => {
+                self.cycle_update_type_filter();
+            }
+            (KeyCode::Char('r'), _) => {
+                return Ok(Event::RefreshFailed);
+            }
+            (KeyCode::Char('R'), _) => {
+                return Ok(Event::RefreshAll);
+            }
+            (KeyCode::Char('d'), _) => {
+                self.show_downloads = !self.show_downloads;
+            }
+            (KeyCode::Char('b'), _) => {
+                self.show_releases_behind = !self.show_releases_behind;
+            }
+            (KeyCode::Char('s'), _) => {
+                self.set_sort_order(self.sort_order.next());
+            }
+            (KeyCode::Char('o'), _) => {
+                let dependency = &mut self.outdated_deps.dependencies[self.cursor_location];
+                dependency.operator = VersionOperator::cycle(dependency.operator);
             }
+            (KeyCode::Char('t'), _) => {
+                self.show_relative_dates = !self.show_relative_dates;
+            }
+            (KeyCode::Char('v'), _) => {
+                self.show_details = true;
+            }
+            (KeyCode::Char('?'), _) => {
+                self.show_legend = !self.show_legend;
+            }
+            (KeyCode::Char('c'), KeyModifiers::NONE) => {
+                self.copy_add_command()?;
+            }
+            (KeyCode::Esc | KeyCode::Char('q'), _)
+            | (KeyCode::Char('c') | KeyCode::Char('z'), KeyModifiers::CONTROL) => {
+                return Ok(Event::Exit);
+            }
+            _ => {}
         }
 
         Ok(Event::HandleKeyboard)
     }
 
-    fn reset_terminal(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        execute!(self.stdout, Show, ResetColor)?;
-        disable_raw_mode()?;
+    /// Sends the `cargo add` command updating the dependency under the cursor to the terminal
+    /// clipboard, for those who would rather run it themselves. Whether the terminal put it in the
+    /// clipboard can't be known, so the command is shown in the footer to be copied by hand
+    fn copy_add_command(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let dependency = &self.outdated_deps.dependencies[self.cursor_location];
+        self.status = Some(match format_add_command(dependency) {
+            Some(command) => {
+                crate::clipboard::copy(&mut self.stdout, &command)?;
+                format!(
+                    "Sent to the terminal clipboard (OSC 52): {}",
+                    command.as_str().bold()
+                )
+            }
+            None => format!(
+                "{} dependencies cannot be updated with cargo add",
+                get_dependencies_subsection_title(dependency.kind)
+            ),
+        });
         Ok(())
     }
 
+    /// Names of the dependencies that could not be checked, with the reason, to check them again
+    /// with [`Event::RefreshFailed`]
+    pub fn set_failed_deps(&mut self, failed_deps: Vec<(String, String)>) {
+        self.failed_deps = failed_deps;
+    }
+
+    pub fn failed_names(&self) -> Vec<String> {
+        self.failed_deps
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Replaces the dependencies with the ones of a new scan, keeping the selection and the cursor
+    /// on the same dependencies
+    pub fn refresh_all(&mut self, result: ScanResult) {
+        // A crate can be declared in several sections or members, each one keeps its selection
+        let selected_keys = self
+            .outdated_deps
+            .iter()
+            .zip(&self.selected)
+            .filter(|(_, selected)| **selected)
+            .map(|(dep, _)| get_dependency_key(dep))
+            .collect::<HashSet<_>>();
+        let selected = result
+            .outdated_deps
+            .iter()
+            .map(|dep| {
+                selected_keys.contains(&get_dependency_key(dep)) || !dep.advisories.is_empty()
+            })
+            .collect();
+
+        self.total_deps = result.total_deps;
+        self.up_to_date_deps = result.up_to_date_deps;
+        self.transitive_deps = result.transitive_deps;
+        self.unchecked_deps = result.unchecked_deps;
+        self.failed_deps = result.failed_deps;
+        self.replace_dependencies(result.outdated_deps.dependencies, selected);
+    }
+
+    /// Adds the dependencies of a scan limited to the ones that could not be checked, leaving the
+    /// others untouched
+    pub fn refresh_failed(&mut self, result: ScanResult) {
+        let mut dependencies = std::mem::take(&mut self.outdated_deps.dependencies);
+        let mut selected = std::mem::take(&mut self.selected);
+        for dependency in result.outdated_deps.dependencies {
+            selected.push(!dependency.advisories.is_empty());
+            dependencies.push(dependency);
+        }
+
+        self.up_to_date_deps.extend(result.up_to_date_deps);
+        self.failed_deps = result.failed_deps;
+        self.replace_dependencies(dependencies, selected);
+    }
+
+    /// Sets the dependencies and their selection, sorted with the current order and with the
+    /// cursor on the same dependency if it is still outdated
+    fn replace_dependencies(&mut self, dependencies: Vec<Dependency>, selected: Vec<bool>) {
+        let cursor = self
+            .outdated_deps
+            .dependencies
+            .get(self.cursor_location)
+            .map(get_dependency_key);
+
+        self.outdated_deps.dependencies = dependencies;
+        self.selected = selected;
+        self.longest_attributes = Longest::get_longest_attributes(&self.outdated_deps);
+        self.update_type_counts = UpdateTypeCounts::count(self.outdated_deps.iter());
+        self.cursor_location = cursor
+            .and_then(|cursor| {
+                self.outdated_deps
+                    .iter()
+                    .position(|d| get_dependency_key(d) == cursor)
+            })
+            .unwrap_or(0);
+
+        if !self.outdated_deps.is_empty() {
+            self.set_sort_order(self.sort_order);
+        }
+    }
+
+    pub fn set_manifest_dir(&mut self, manifest_dir: &Path) {
+        self.manifest_dir = manifest_dir.to_path_buf();
+    }
+
+    /// Sorts the dependencies within their kind, keeping the selection and the cursor on the same
+    /// dependencies
+    pub fn set_sort_order(&mut self, sort_order: SortOrder) {
+        self.sort_order = sort_order;
+
+        let cursor = get_dependency_key(&self.outdated_deps.dependencies[self.cursor_location]);
+
+        let mut rows = std::mem::take(&mut self.outdated_deps.dependencies)
+            .into_iter()
+            .zip(std::mem::take(&mut self.selected))
+            .collect::<Vec<_>>();
+        rows.sort_by(|(a, _), (b, _)| sort_order.compare(a, b));
+
+        (self.outdated_deps.dependencies, self.selected) = rows.into_iter().unzip();
+        self.cursor_location = self
+            .outdated_deps
+            .iter()
+            .position(|d| get_dependency_key(d) == cursor)
+            .unwrap_or(0);
+    }
+
+    fn is_visible(&self, i: usize) -> bool {
+        self.update_type_filter
+            .is_none_or(|filter| self.outdated_deps.dependencies[i].update_type() == Some(filter))
+            && (!self.show_selected_only || self.selected[i])
+    }
+
+    /// Indices of the dependencies shown with the active update type filter and selected only view
+    fn visible_indices(&self) -> Vec<usize> {
+        (0..self.outdated_deps.len())
+            .filter(|&i| self.is_visible(i))
+            .collect()
+    }
+
+    /// Moves the cursor to another visible dependency, computed from the position of the cursor
+    /// among the visible dependencies and their number
+    fn move_cursor(&mut self, new_position: impl FnOnce(usize, usize) -> usize) {
+        let visible_indices = self.visible_indices();
+        if visible_indices.is_empty() {
+            return;
+        }
+
+        let position = visible_indices
+            .iter()
+            .position(|&i| i == self.cursor_location)
+            .unwrap_or(0);
+        let new_position = new_position(position, visible_indices.len());
+        self.cursor_location = visible_indices[new_position.min(visible_indices.len() - 1)];
+    }
+
+    /// Shows only the patch, then minor, then major updates, then all of them again, skipping the
+    /// update types without any dependency
+    fn cycle_update_type_filter(&mut self) {
+        let count = |update_type| match update_type {
+            UpdateType::Patch => self.update_type_counts.patch,
+            UpdateType::Minor => self.update_type_counts.minor,
+            UpdateType::Major => self.update_type_counts.major,
+        };
+
+        let mut filter = self.update_type_filter;
+        loop {
+            filter = match filter {
+                None => Some(UpdateType::Patch),
+                Some(UpdateType::Patch) => Some(UpdateType::Minor),
+                Some(UpdateType::Minor) => Some(UpdateType::Major),
+                Some(UpdateType::Major) => None,
+            };
+
+            if filter.is_none_or(|filter| count(filter) > 0) {
+                break;
+            }
+        }
+        self.update_type_filter = filter;
+
+        if !self.is_visible(self.cursor_location) {
+            self.move_cursor(|_, _| 0);
+        }
+    }
+
+    /// Shows only the selected dependencies, or all of them again with the cursor left on the same
+    /// dependency
+    fn toggle_selected_only(&mut self) {
+        if !self.show_selected_only && !self.selected.iter().any(|selected| *selected) {
+            self.status = Some("No dependencies are selected yet".to_string());
+            return;
+        }
+
+        self.show_selected_only = !self.show_selected_only;
+        self.keep_cursor_visible();
+    }
+
+    /// Moves the cursor to the nearest visible dependency after it, or before it when there is
+    /// none, once it was hidden, e.g. deselected while only the selected ones are shown. The
+    /// selected only view is left when there are no more selected dependencies to show
+    fn keep_cursor_visible(&mut self) {
+        if self.is_visible(self.cursor_location) {
+            return;
+        }
+
+        let visible_indices = self.visible_indices();
+        if visible_indices.is_empty() {
+            self.show_selected_only = false;
+            return;
+        }
+
+        self.cursor_location = visible_indices
+            .iter()
+            .copied()
+            .find(|&i| i > self.cursor_location)
+            .unwrap_or(visible_indices[visible_indices.len() - 1]);
+    }
+
+    /// Moves the cursor to the first dependency starting with the name typed so far, if any
+    fn jump(&mut self) {
+        if self.jump_buffer.is_empty() {
+            return;
+        }
+
+        let visible_dependencies = self
+            .outdated_deps
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.is_visible(*i));
+
+        if let Some(i) = find_by_name_prefix(visible_dependencies, &self.jump_buffer) {
+            self.cursor_location = i;
+        }
+    }
+
+    /// Number of dependencies moved by page up/down, the rows fitting in the terminal besides the
+    /// header and footer
+    fn page_size(&self) -> usize {
+        self.terminal_height.saturating_sub(6).max(1)
+    }
+
+    pub fn selected_names(&self) -> Vec<&str> {
+        self.outdated_deps
+            .iter()
+            .zip(self.selected.iter())
+            .filter(|(_, selected)| **selected)
+            .map(|(dep, _)| dep.name.as_str())
+            .collect()
+    }
+
     pub fn selected_dependencies(self) -> Dependencies {
         self.outdated_deps
             .filter_selected_dependencies(self.selected)
     }
 
     pub fn render(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        self.render_header()?;
-        self.render_dependencies()?;
-        self.render_footer_actions()?;
+        let (terminal_width, terminal_height) = size()?;
+        self.terminal_width = terminal_width as usize;
+        self.terminal_height = terminal_height as usize;
+
+        let mut frame = Frame::new(self.terminal_width, self.terminal_height);
+        self.render_header(&mut frame);
+        if self.show_details {
+            self.render_details(&mut frame);
+        } else {
+            self.render_dependencies(&mut frame);
+            self.render_footer_actions(&mut frame);
+        }
+
+        frame.draw(&mut self.stdout, self.previous_frame.as_ref())?;
+        self.previous_frame = Some(frame);
 
         self.stdout.flush()?;
         Ok(())
     }
 
-    fn render_header(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        execute!(
-            self.stdout,
-            Clear(ClearType::All),
-            MoveTo(0, 0),
-            Print(format!(
-                "{} out of the {} direct dependencies are outdated.",
-                self.outdated_deps.len().to_string().bold(),
-                self.total_deps.to_string().bold()
-            )),
-            MoveToNextLine(1)
-        )?;
-        Ok(())
-    }
+    fn render_header(&self, frame: &mut Frame) {
+        let mut header = format!(
+            "{} out of the {} direct dependencies are outdated ({} patch, {} minor, {} major).",
+            self.outdated_deps.len().to_string().bold(),
+            self.total_deps.to_string().bold(),
+            self.update_type_counts.patch.to_string().green(),
+            self.update_type_counts.minor.to_string().yellow(),
+            self.update_type_counts.major.to_string().red(),
+        );
+
+        if self.unchecked_deps > 0 {
+            header.push_str(&format!(
+                "{}",
+                format!(" {} were not checked due to timeout.", self.unchecked_deps).yellow()
+            ));
+        }
+
+        if !self.failed_deps.is_empty() {
+            header.push_str(&format!(
+                "{}",
+                format!(" {} could not be checked.", self.failed_deps.len()).yellow()
+            ));
+        }
+
+        if self.update_type_filter.is_some() || self.show_selected_only {
+            let label = match self.update_type_filter {
+                Some(UpdateType::Patch) => " patch",
+                Some(UpdateType::Minor) => " minor",
+                Some(UpdateType::Major) => " major",
+                None => "",
+            };
+            let selected = if self.show_selected_only {
+                " selected"
+            } else {
+                ""
+            };
+
+            let shown = self.visible_indices().len();
 
-    fn render_dependencies(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let mut offset = 0;
+            header.push_str(&format!(
+                "{}",
+                format!(" Showing only the {shown}{selected}{label} updates.").bold()
+            ));
+        }
 
-        execute!(self.stdout, DisableLineWrap)?;
+        frame.wrapped_line(header);
+    }
 
+    fn render_dependencies(&self, frame: &mut Frame) {
+        // The column headers are only shown above the first subsection
+        let mut with_headers = true;
         for kind in DependencyKind::ordered() {
-            offset += self.render_dependencies_subsection(kind, offset)?;
+            if self.render_dependencies_subsection(frame, kind, with_headers) {
+                with_headers = false;
+            }
         }
 
-        execute!(self.stdout, EnableLineWrap)?;
+        self.render_transitive_dependencies(frame);
+        self.render_up_to_date_dependencies(frame);
+    }
 
-        Ok(())
+    fn render_transitive_dependencies(&self, frame: &mut Frame) {
+        if self.transitive_deps.is_empty() {
+            return;
+        }
+
+        frame.blank();
+        frame.line(
+            format!(
+                "Transitive dependencies ({} outdated, can't be selected):",
+                self.transitive_deps.len()
+            )
+            .cyan()
+            .dim(),
+        );
+
+        let name_width = self.transitive_deps.iter().map(|d| d.name.len()).max();
+
+        for dependency in &self.transitive_deps {
+            let row = format_transitive_dependency(dependency, name_width.unwrap_or_default());
+            frame.line(row.dim());
+        }
+    }
+
+    fn render_up_to_date_dependencies(&self, frame: &mut Frame) {
+        if self.up_to_date_deps.is_empty() {
+            return;
+        }
+
+        frame.blank();
+        frame.line(
+            format!("Up to date ({}):", self.up_to_date_deps.len())
+                .cyan()
+                .dim(),
+        );
+
+        let name_width = self.up_to_date_deps.iter().map(|d| d.name.len()).max();
+
+        for dependency in &self.up_to_date_deps {
+            let row = format_up_to_date_dependency(
+                dependency,
+                name_width.unwrap_or_default(),
+                &self.format_date(dependency.current_version_date.as_deref()),
+                self.terminal_width,
+            );
+            frame.line(row.dim());
+        }
     }
 
+    /// Renders the visible dependencies of the given kind, returning whether there were any
     fn render_dependencies_subsection(
-        &mut self,
+        &self,
+        frame: &mut Frame,
         kind: DependencyKind,
-        offset: usize,
-    ) -> Result<usize, Box<dyn std::error::Error>> {
+        with_headers: bool,
+    ) -> bool {
         let deps = self
             .outdated_deps
             .iter()
-            .filter(|dep| dep.kind == kind)
-            .cloned()
+            .enumerate()
+            .filter(|(i, dep)| dep.kind == kind && self.is_visible(*i))
             .collect::<Vec<_>>();
 
         if deps.is_empty() {
-            return Ok(0);
+            return false;
         }
 
         let title = get_dependencies_subsection_title(kind);
-        let num_selected = self
+        let selected = self
             .selected
             .iter()
             .zip(self.outdated_deps.iter())
             .filter(|(selected, dep)| **selected && dep.kind == kind)
-            .count();
-
-        execute!(
-            self.stdout,
-            MoveToNextLine(1),
-            PrintStyledContent(format!("{title} ({num_selected} selected):").cyan()),
-            MoveToNextLine(1)
-        )?;
-
-        for (i, dependency) in deps.iter().enumerate() {
-            self.render_dependency(i + offset, dependency)?;
-        }
-
-        Ok(deps.len())
-    }
-
-    fn render_footer_actions(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        execute!(
-            self.stdout,
-            MoveToNextLine(2),
-            Print(format!(
-                "Use {} to navigate, {} to select all, {} to invert, {} to select/deselect, {} to update, {}/{} to exit",
-                "arrow keys".cyan(),
-                "<a>".cyan(),
-                "<i>".cyan(),
-                "<space>".cyan(),
-                "<enter>".cyan(),
-                "<esc>".cyan(), "<q>".cyan()
-            ))
-        )?;
-        Ok(())
+            .map(|(_, dep)| dep)
+            .collect::<Vec<_>>();
+        let selection = format_selection(selected.len(), &UpdateTypeCounts::count(selected));
+
+        frame.blank();
+        frame.line(format!("{title} ({selection}):").cyan());
+
+        if with_headers {
+            let headers = format_column_headers(
+                &self.longest_attributes,
+                self.outdated_deps.has_workspace_members(),
+                self.show_downloads,
+                self.show_releases_behind,
+            );
+            frame.line(headers.dim());
+        }
+
+        for (i, dependency) in deps {
+            frame.line(self.format_dependency(i, dependency));
+        }
+
+        true
+    }
+
+    fn render_footer_actions(&self, frame: &mut Frame) {
+        frame.blank();
+        if self.show_legend {
+            frame.blank();
+            for line in format_key_reference(self.sort_order) {
+                frame.line(line);
+            }
+            frame.blank();
+            frame.wrapped_line(format_legend());
+        }
+        frame.blank();
+        frame.wrapped_line(format!(
+            "Use {} to navigate, {} to select/deselect, {} to update, {}/{} to exit, {} to show/hide all the keys and the legend",
+            "arrow keys".cyan(),
+            "<space>".cyan(),
+            "<enter>".cyan(),
+            "<esc>".cyan(),
+            "<q>".cyan(),
+            "<?>".cyan()
+        ));
+        if self.jumping {
+            frame.blank();
+            frame.line(format!("{} {}", "Jump to:".bold(), self.jump_buffer));
+        }
+        if let Some(status) = &self.status {
+            frame.blank();
+            for line in status.lines() {
+                frame.wrapped_line(line);
+            }
+        }
+    }
+
+    /// Everything known about the dependency under the cursor, with its full description and the
+    /// versions released since the current one
+    fn render_details(&self, frame: &mut Frame) {
+        let dependency = &self.outdated_deps.dependencies[self.cursor_location];
+        let date = |datetime_string: &Option<String>| {
+            get_date_from_datetime_string(datetime_string.as_deref())
+                .unwrap_or("unknown")
+                .to_string()
+        };
+
+        let mut lines = vec![
+            format!(
+                "{} ({})",
+                dependency.name.as_str().bold(),
+                get_dependencies_subsection_title(dependency.kind)
+            ),
+            String::new(),
+            format!(
+                "Current: {} ({}), released {}",
+                dependency.current_version,
+                dependency.requirement,
+                date(&dependency.current_version_date)
+            ),
+            format!(
+                "Latest: {}, released {}",
+                dependency.latest_version,
+                date(&dependency.latest_version_date)
+            ),
+        ];
+
+        if let Some(package_name) = dependency
+            .package_name
+            .as_deref()
+            .filter(|_| self.outdated_deps.has_workspace_members())
+        {
+            lines.push(format!("Package: {package_name}"));
+        }
+        let manifest_path = self
+            .manifest_dir
+            .join(dependency.workspace_path.as_deref().unwrap_or("."))
+            .join("Cargo.toml");
+        lines.push(format!(
+            "Manifest: {}",
+            format_manifest_path(&manifest_path, self.hyperlinks)
+        ));
+        lines.push(format!(
+            "Repository: {}",
+            dependency.repository.as_deref().unwrap_or("none")
+        ));
+        lines.push(format!(
+            "Downloads: {} ({} recent)",
+            dependency
+                .downloads
+                .map_or("n/a".to_string(), format_downloads),
+            dependency
+                .recent_downloads
+                .map_or("n/a".to_string(), format_downloads)
+        ));
+        if dependency.is_deprecated() {
+            lines.push(format!("{}", "DEPRECATED".yellow().bold()));
+        }
+        if dependency.current_version_yanked {
+            lines.push(format!("{}", "The current version was yanked".red().bold()));
+        }
+        if dependency.patched {
+            lines.push(format!(
+                "{}",
+                "Patched in [patch.crates-io], the patch decides the version used"
+                    .cyan()
+                    .bold()
+            ));
+        }
+        if let Some(rust_version) = &dependency.latest_rust_version {
+            lines.push(format!(
+                "{}",
+                format!("Requires Rust {rust_version}, newer than the rust-version of the package")
+                    .magenta()
+                    .bold()
+            ));
+        }
+        if !dependency.advisories.is_empty() {
+            lines.push(format!(
+                "{}",
+                format!("Advisories: {}", dependency.advisories.join(", "))
+                    .red()
+                    .bold()
+            ));
+        }
+        lines.push(String::new());
+        lines.push(
+            dependency
+                .description
+                .clone()
+                .unwrap_or_else(|| "No description".to_string()),
+        );
+        lines.push(String::new());
+        lines.push(format!(
+            "Versions in between ({}): {}",
+            dependency.intermediate_versions.len(),
+            format_intermediate_versions(dependency, self.show_yanked)
+        ));
+        if !self.show_yanked && !dependency.yanked_versions.is_empty() {
+            lines.push(format!(
+                "{} yanked versions hidden",
+                dependency.yanked_versions.len()
+            ));
+        }
+
+        frame.blank();
+        for line in lines {
+            frame.wrapped_line(line);
+        }
+        frame.blank();
+        frame.wrapped_line(format!(
+            "Use {} to show/hide the yanked versions, {}/{} to go back to the list",
+            "<y>".cyan(),
+            "<esc>".cyan(),
+            "<v>".cyan()
+        ));
     }
 
-    fn render_dependency(
-        &mut self,
+    /// Row of the dependency in the list, highlighted when under the cursor
+    fn format_dependency(
+        &self,
         i: usize,
-        Dependency {
+        dependency @ Dependency {
             name,
-            current_version,
+            requirement,
             latest_version,
             repository,
             description,
             latest_version_date,
             current_version_date,
+            recent_downloads,
+            operator,
+            advisories,
             package_name,
             ..
         }: &Dependency,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> StyledContent<String> {
         let name_spacing = " ".repeat(self.longest_attributes.name - name.len());
-        let current_version_spacing =
-            " ".repeat(self.longest_attributes.current_version - current_version.len());
-        let latest_version_spacing =
-            " ".repeat(self.longest_attributes.latest_version - latest_version.len());
+        let requirement_spacing =
+            " ".repeat(self.longest_attributes.requirement - requirement.len());
+        let operator = operator.map_or("", VersionOperator::prefix);
+        let latest_version_spacing = " ".repeat(
+            self.longest_attributes.latest_version + 1 - operator.len() - latest_version.len(),
+        );
 
         let bullet = if self.selected[i] { "●" } else { "○" };
 
-        let latest_version_date = get_date_from_datetime_string(latest_version_date.as_deref())
-            .unwrap_or("          ")
+        let latest_version_date = self
+            .format_date(latest_version_date.as_deref())
             .italic()
             .dim();
-        let current_version_date = get_date_from_datetime_string(current_version_date.as_deref())
-            .unwrap_or("          ")
+        let current_version_date = self
+            .format_date(current_version_date.as_deref())
             .italic()
             .dim();
 
+        let repository = repository.as_deref().unwrap_or("none");
+        let advisories = if advisories.is_empty() {
+            String::new()
+        } else {
+            format!("⚠ {} ", advisories.join(", "))
+        };
+        let deprecated = if dependency.is_deprecated() {
+            "DEPRECATED "
+        } else {
+            ""
+        };
+        let patched = if dependency.patched { "PATCHED " } else { "" };
+        let rust_version = dependency
+            .latest_rust_version
+            .as_deref()
+            .map_or(String::new(), |rust_version| {
+                format!("MSRV {rust_version} ")
+            });
+        let has_workspace_members = self.outdated_deps.has_workspace_members();
+
+        // Everything on the row except the description: bullet, name, package name, both dates
+        // with their versions, the arrow, the downloads, the badges, the repository and the
+        // separators in between
+        let fixed_width = 2
+            + self.longest_attributes.name
+            + 2
+            + if has_workspace_members {
+                self.longest_attributes.package_name + 2
+            } else {
+                0
+            }
+            + 11
+            + self.longest_attributes.requirement
+            + 4
+            + 11
+            + self.longest_attributes.latest_version
+            + 3
+            + if self.show_downloads { 8 } else { 0 }
+            + if self.show_releases_behind { 6 } else { 0 }
+            + deprecated.len()
+            + patched.len()
+            + rust_version.len()
+            + advisories.chars().count()
+            + repository.chars().count()
+            + 3;
+        let description = truncate_to_width(
+            description.as_deref().unwrap_or(""),
+            self.terminal_width.saturating_sub(fixed_width),
+        )
+        .dim();
+
         let name = name.clone().bold();
-        let repository = repository.as_deref().unwrap_or("none").underline_black();
-        let description = description.as_deref().unwrap_or("").dim();
-        let package_name = if self.outdated_deps.has_workspace_members() {
+        let deprecated = deprecated.yellow().bold();
+        let patched = patched.cyan().bold();
+        let rust_version = rust_version.magenta().bold();
+        let advisories = advisories.red().bold();
+        let repository = repository.underline_black();
+        let recent_downloads = if self.show_downloads {
+            format!(
+                "{:>6}  ",
+                recent_downloads.map_or("n/a".to_string(), format_downloads)
+            )
+            .magenta()
+        } else {
+            "".to_string().magenta()
+        };
+        let releases_behind = if self.show_releases_behind {
+            format!("{:>4}  ", dependency.releases_behind()).cyan()
+        } else {
+            "".to_string().cyan()
+        };
+        let package_name = if has_workspace_members {
             let package_name = package_name.as_deref().unwrap_or("");
             let package_name = if package_name.is_empty() {
                 "-".to_string()
@@ -269,55 +1104,808 @@ impl State {
         };
 
         let row = format!(
-            "{bullet} {name}{name_spacing}  {package_name}{current_version_date} {current_version}{current_version_spacing} -> {latest_version_date} {latest_version}{latest_version_spacing}  {repository} - {description}",
+            "{bullet} {name}{name_spacing}  {package_name}{current_version_date} {requirement}{requirement_spacing} -> {latest_version_date} {operator}{latest_version}{latest_version_spacing}  {recent_downloads}{releases_behind}{deprecated}{patched}{rust_version}{advisories}{repository} - {description}",
         );
 
-        let colored_row = if i == self.cursor_location {
+        if i == self.cursor_location {
             row.green()
         } else {
             row.black()
+        }
+    }
+
+    fn format_date(&self, datetime_string: Option<&str>) -> String {
+        let date = if self.show_relative_dates {
+            self.today
+                .and_then(|today| get_relative_date_from_datetime_string(datetime_string, today))
+        } else {
+            None
         };
 
-        execute!(
-            self.stdout,
-            PrintStyledContent(colored_row),
-            MoveToNextLine(1),
-        )?;
-        Ok(())
+        let date =
+            date.or_else(|| get_date_from_datetime_string(datetime_string).map(str::to_string));
+
+        format!("{:10}", date.unwrap_or_default())
     }
 }
 
-fn get_date_from_datetime_string(datetime_string: Option<&str>) -> Option<&str> {
-    datetime_string
-        .and_then(|s| s.split_once('T'))
-        .map(|(date, _)| date)
-}
+/// Number of selected dependencies followed by how many of them are major, minor and patch
+/// updates, e.g. `3 selected: 1 major, 2 patch`
+fn format_selection(num_selected: usize, counts: &UpdateTypeCounts) -> String {
+    let counts = [
+        (counts.major, "major"),
+        (counts.minor, "minor"),
+        (counts.patch, "patch"),
+    ]
+    .into_iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, label)| format!("{count} {label}"))
+    .collect::<Vec<_>>();
 
-fn get_dependencies_subsection_title(kind: DependencyKind) -> &'static str {
-    match kind {
-        DependencyKind::Normal => "Dependencies",
-        DependencyKind::Dev => "Dev dependencies",
-        DependencyKind::Build => "Build dependencies",
-        DependencyKind::Workspace => "Workspace dependencies",
+    if counts.is_empty() {
+        format!("{num_selected} selected")
+    } else {
+        format!("{num_selected} selected: {}", counts.join(", "))
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Header row naming the columns of the dependency rows, aligned with them
+fn format_column_headers(
+    longest: &Longest,
+    has_workspace_members: bool,
+    show_downloads: bool,
+    show_releases_behind: bool,
+) -> String {
+    let package = if has_workspace_members {
+        format!("{:width$}  ", "package", width = longest.package_name)
+    } else {
+        String::new()
+    };
+    let downloads = if show_downloads {
+        format!("{:>6}  ", "recent")
+    } else {
+        String::new()
+    };
+    let releases_behind = if show_releases_behind {
+        format!("{:>4}  ", "rel.")
+    } else {
+        String::new()
+    };
 
-    #[test]
-    fn test_get_longest_attributes() {
-        let dependencies = Dependencies::new(
-            vec![
-                Dependency {
-                    name: "short".to_string(),
-                    current_version: "1".to_string(),
-                    latest_version: "2".to_string(),
-                    ..Default::default()
+    format!(
+        "  {:name_width$}  {package}{:current_width$} -> {:latest_width$}  {downloads}{releases_behind}repository - description",
+        "name",
+        "current (date)",
+        "latest (date)",
+        name_width = longest.name,
+        current_width = 11 + longest.requirement,
+        latest_width = 12 + longest.latest_version,
+    )
+}
+
+pub fn print_up_to_date_dependencies(dependencies: &[Dependency]) {
+    if dependencies.is_empty() {
+        return;
+    }
+
+    println!("\n{}:", "Up to date".cyan());
+
+    let name_width = dependencies.iter().map(|d| d.name.len()).max();
+
+    for dependency in dependencies {
+        let date = get_date_from_datetime_string(dependency.current_version_date.as_deref());
+        println!(
+            "{}",
+            format_up_to_date_dependency(
+                dependency,
+                name_width.unwrap_or_default(),
+                &format!("{:10}", date.unwrap_or_default()),
+                usize::MAX,
+            )
+            .dim()
+        );
+    }
+}
+
+/// Prints the outdated packages of Cargo.lock that are not direct dependencies, for reference only
+/// as they can't be selected
+pub fn print_transitive_dependencies(dependencies: &[Dependency]) {
+    if dependencies.is_empty() {
+        return;
+    }
+
+    println!(
+        "\n{}:",
+        format!(
+            "{} outdated transitive dependencies, which can't be updated in Cargo.toml",
+            dependencies.len()
+        )
+        .cyan()
+    );
+
+    let name_width = dependencies.iter().map(|d| d.name.len()).max();
+
+    for dependency in dependencies {
+        println!(
+            "{}",
+            format_transitive_dependency(dependency, name_width.unwrap_or_default()).dim()
+        );
+    }
+}
+
+fn format_transitive_dependency(
+    Dependency {
+        name,
+        current_version,
+        latest_version,
+        advisories,
+        ..
+    }: &Dependency,
+    name_width: usize,
+) -> String {
+    let advisories = if advisories.is_empty() {
+        String::new()
+    } else {
+        format!("  ⚠ {}", advisories.join(", "))
+    };
+
+    format!("  {name:name_width$}  {current_version} -> {latest_version}{advisories}")
+}
+
+/// Row of a dependency that is already up to date, shown only for reference
+fn format_up_to_date_dependency(
+    Dependency {
+        name,
+        requirement,
+        repository,
+        description,
+        ..
+    }: &Dependency,
+    name_width: usize,
+    date: &str,
+    terminal_width: usize,
+) -> String {
+    let row = format!(
+        "  {name:name_width$}  {date} {requirement}  {} - ",
+        repository.as_deref().unwrap_or("none")
+    );
+    let description = truncate_to_width(
+        description.as_deref().unwrap_or(""),
+        terminal_width.saturating_sub(row.chars().count()),
+    );
+
+    format!("{row}{description}")
+}
+
+/// Identifies a dependency among the ones of the same crate declared in other sections or members
+fn get_dependency_key(dependency: &Dependency) -> (String, DependencyKind, Option<String>) {
+    (
+        dependency.name.clone(),
+        dependency.kind,
+        dependency.workspace_path.clone(),
+    )
+}
+
+/// Every key and what it does, one per line, shown along with the legend
+fn format_key_reference(sort_order: SortOrder) -> Vec<String> {
+    let sort = format!("change the sort order ({})", sort_order.label());
+    let keys = [
+        ("arrow keys", "navigate"),
+        ("<pgup>/<pgdn>", "move by page"),
+        ("<home>/<end>", "go to the first/last"),
+        ("<n>/<N>", "jump to the next/previous unselected"),
+        ("<space>", "select/deselect"),
+        ("<a>/<A>", "select/deselect all"),
+        ("<i>", "invert the selection"),
+        ("<f>", "filter by update type"),
+        ("<tab>", "show only the selected/all"),
+        ("</>", "type a name to jump to it"),
+        ("<d>", "toggle downloads"),
+        ("<b>", "toggle releases behind"),
+        ("<s>", sort.as_str()),
+        ("<t>", "toggle dates"),
+        ("<o>", "change the version operator"),
+        ("<v>", "view details"),
+        (
+            "<c>",
+            "send the cargo add command to the terminal clipboard",
+        ),
+        ("<r>/<R>", "check again the failed/all dependencies"),
+        ("<?>", "show/hide the keys and the legend"),
+        ("<enter>", "update"),
+        ("<esc>/<q>", "exit"),
+    ];
+    let width = keys.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+
+    keys.iter()
+        .map(|(key, action)| format!("{}  {action}", format!("{key:width$}").cyan()))
+        .collect()
+}
+
+/// What the bullets, colors and badges of the list stand for
+fn format_legend() -> String {
+    format!(
+        "Legend: ● selected, ○ not selected, {} row under the cursor, {}/{}/{} counts of patch/minor/major updates, {} security advisories affecting the current version, {} crate, {} crate overridden in [patch.crates-io], {} newer Rust version required, {} package of the workspace member, yanked versions {} in the details",
+        "green".green(),
+        "green".green(),
+        "yellow".yellow(),
+        "red".red(),
+        "⚠".red().bold(),
+        "DEPRECATED".yellow().bold(),
+        "PATCHED".cyan().bold(),
+        "MSRV".magenta().bold(),
+        "blue".blue().italic(),
+        "crossed out".crossed_out()
+    )
+}
+
+/// Whether applying the dependencies at once with `--yes` calls for a confirmation first, being
+/// more than `max_changes` or including a major update
+pub fn exceeds_changes_limit(dependencies: &Dependencies, max_changes: usize) -> bool {
+    dependencies.len() > max_changes || dependencies.iter().any(Dependency::is_major_update)
+}
+
+/// Asks a yes or no question on the terminal, defaulting to no, and without asking when the input
+/// is not a terminal, e.g. in a CI job
+pub fn confirm(question: &str) -> std::io::Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        return Ok(false);
+    }
+
+    print!("{question} [y/N] ");
+    stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+pub fn print_dependencies(dependencies: &Dependencies) {
+    let longest_attributes = Longest::get_longest_attributes(dependencies);
+
+    for kind in DependencyKind::ordered() {
+        let mut deps = dependencies
+            .iter()
+            .filter(|dep| dep.kind == kind)
+            .peekable();
+
+        if deps.peek().is_none() {
+            continue;
+        }
+
+        println!("\n{}:", get_dependencies_subsection_title(kind).cyan());
+
+        for dependency @ Dependency {
+            name,
+            requirement,
+            latest_version,
+            package_name,
+            ..
+        } in deps
+        {
+            let package_name = if dependencies.has_workspace_members() {
+                format!(
+                    "{:width$}  ",
+                    package_name
+                        .as_deref()
+                        .filter(|p| !p.is_empty())
+                        .unwrap_or("-"),
+                    width = longest_attributes.package_name
+                )
+            } else {
+                String::new()
+            };
+
+            println!(
+                "{}  {package_name}{:requirement_width$} -> {latest_version}{}{}{}",
+                // The width of a styled content is ignored when displaying it
+                format!("{name:width$}", width = longest_attributes.name).bold(),
+                requirement,
+                if dependency.is_deprecated() {
+                    "  DEPRECATED".yellow().bold()
+                } else {
+                    "".yellow()
+                },
+                if dependency.patched {
+                    "  PATCHED".cyan().bold()
+                } else {
+                    "".cyan()
+                },
+                dependency
+                    .latest_rust_version
+                    .as_deref()
+                    .map_or(String::new(), |rust_version| format!(
+                        "  MSRV {rust_version}"
+                    ))
+                    .magenta()
+                    .bold(),
+                requirement_width = longest_attributes.requirement,
+            );
+        }
+    }
+}
+
+/// Versions released between the current and the latest one, along with the crossed out yanked
+/// ones with `show_yanked`
+fn format_intermediate_versions(dependency: &Dependency, show_yanked: bool) -> String {
+    let mut versions = dependency
+        .intermediate_versions
+        .iter()
+        .map(|version| (version, false))
+        .collect::<Vec<_>>();
+    if show_yanked {
+        versions.extend(
+            dependency
+                .yanked_versions
+                .iter()
+                .map(|version| (version, true)),
+        );
+    }
+
+    if versions.is_empty() {
+        return "none".to_string();
+    }
+
+    versions.sort_by_key(|(version, _)| semver::Version::parse(version).ok());
+    versions
+        .into_iter()
+        .map(|(version, yanked)| {
+            if yanked {
+                version.as_str().crossed_out().to_string()
+            } else {
+                version.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `cargo add` command bumping the dependency to its latest version with the chosen operator, in
+/// the workspace member declaring it. Workspace dependencies are not added to a package, so there
+/// is no such command for them
+fn format_add_command(dependency: &Dependency) -> Option<String> {
+    let flag = match dependency.kind {
+        DependencyKind::Normal => "",
+        DependencyKind::Dev => " --dev",
+        DependencyKind::Build => " --build",
+        DependencyKind::Workspace => return None,
+    };
+    let package = match (&dependency.package_name, &dependency.workspace_path) {
+        (Some(package_name), Some(_)) => format!(" -p {package_name}"),
+        _ => String::new(),
+    };
+
+    Some(format!(
+        "cargo add {}@{}{flag}{package}",
+        dependency.name,
+        dependency.version_requirement(false)
+    ))
+}
+
+/// Path of the Cargo.toml file relative to the current directory, as a link to the file when the
+/// terminal supports it
+fn format_manifest_path(path: &Path, hyperlinks: bool) -> String {
+    let Ok(absolute_path) = path.canonicalize() else {
+        return path.display().to_string();
+    };
+    let text = match std::env::current_dir().and_then(|dir| dir.canonicalize()) {
+        Ok(current_dir) => get_relative_path(&absolute_path, &current_dir)
+            .display()
+            .to_string(),
+        Err(_) => path.display().to_string(),
+    };
+
+    if hyperlinks {
+        crate::hyperlink::link(&text, &crate::hyperlink::file_url(&absolute_path))
+    } else {
+        text
+    }
+}
+
+/// Path relative to the given base, both being absolute, going up with `..` when it is outside
+fn get_relative_path(path: &Path, base: &Path) -> PathBuf {
+    let path_components = path.components().collect::<Vec<_>>();
+    let base_components = base.components().collect::<Vec<_>>();
+    let common = path_components
+        .iter()
+        .zip(&base_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common == 0 {
+        return path.to_path_buf();
+    }
+
+    std::iter::repeat_n(Component::ParentDir, base_components.len() - common)
+        .chain(path_components[common..].iter().copied())
+        .collect()
+}
+
+fn get_date_from_datetime_string(datetime_string: Option<&str>) -> Option<&str> {
+    datetime_string
+        .and_then(|s| s.split_once('T'))
+        .map(|(date, _)| date)
+}
+
+fn get_dependencies_subsection_title(kind: DependencyKind) -> &'static str {
+    match kind {
+        DependencyKind::Normal => "Dependencies",
+        DependencyKind::Dev => "Dev dependencies",
+        DependencyKind::Build => "Build dependencies",
+        DependencyKind::Workspace => "Workspace dependencies",
+    }
+}
+
+fn get_kind_name(kind: DependencyKind) -> &'static str {
+    match kind {
+        DependencyKind::Normal => "normal",
+        DependencyKind::Dev => "dev",
+        DependencyKind::Build => "build",
+        DependencyKind::Workspace => "workspace",
+    }
+}
+
+/// Prints the outdated dependencies one per line without any styling, for piping into other tools
+pub fn print_list(dependencies: &Dependencies) {
+    for dependency in dependencies.iter() {
+        println!("{}", format_list_line(dependency));
+    }
+}
+
+fn format_list_line(
+    Dependency {
+        name,
+        current_version,
+        latest_version,
+        kind,
+        ..
+    }: &Dependency,
+) -> String {
+    format!(
+        "{name} {current_version} -> {latest_version} ({})",
+        get_kind_name(*kind)
+    )
+}
+
+/// Prints the outdated dependencies as a GitHub-flavored Markdown table
+pub fn print_markdown(dependencies: &Dependencies) {
+    print!("{}", format_markdown_table(dependencies));
+}
+
+fn format_markdown_table(dependencies: &Dependencies) -> String {
+    // Pipes would end the cell early
+    let escape = |text: &str| text.replace('|', "\\|");
+
+    let mut table =
+        "| Crate | Current | Latest | Kind | Repository |\n| --- | --- | --- | --- | --- |\n"
+            .to_string();
+
+    for dependency in dependencies.iter() {
+        let repository = dependency
+            .repository
+            .as_deref()
+            .map_or("-".to_string(), |repository| {
+                format!("[{}]({repository})", escape(repository))
+            });
+
+        table.push_str(&format!(
+            "| {} | {} | {} | {} | {repository} |\n",
+            escape(&dependency.name),
+            escape(&dependency.current_version),
+            escape(&dependency.latest_version),
+            get_kind_name(dependency.kind),
+        ));
+    }
+
+    table
+}
+
+/// Declared dependencies that were not checked, by reason, e.g. `Skipped 2 excluded (bar, foo), 1
+/// without a version (local).`
+/// Prints what [`cargo_interactive_update::apply`] did, along with the output of `cargo check`
+pub fn print_apply_report(report: &ApplyReport, options: &ApplyOptions) {
+    println!("\n\n");
+
+    if report.updated.is_empty() && report.skipped.is_empty() {
+        println!("No dependencies have been updated.");
+        return;
+    }
+
+    if let Some(output_patch) = &options.output_patch {
+        println!(
+            "Wrote the updates of {} dependencies to {}",
+            report.updated.len().to_string().bold(),
+            output_patch.display()
+        );
+        return;
+    }
+
+    if let Some(output_dir) = &options.output_dir {
+        println!(
+            "Wrote the updates of {} dependencies to {} Cargo.toml files under {}",
+            report.updated.len().to_string().bold(),
+            report.written_files.len(),
+            output_dir.display()
+        );
+        return;
+    }
+
+    if options.lock_only {
+        println!(
+            "Updated {} dependencies in Cargo.lock",
+            report.updated.len().to_string().bold()
+        );
+        for update in &report.updated {
+            println!("  {}: {}", update.name, update.new_requirement);
+        }
+
+        if !report.skipped.is_empty() {
+            println!(
+                "\n{}",
+                format!(
+                    "Skipped {} updates needing a new requirement in Cargo.toml:",
+                    report.skipped.len()
+                )
+                .yellow()
+            );
+            for update in &report.skipped {
+                println!(
+                    "  {}: {} -> {}",
+                    update.name, update.requirement, update.new_requirement
+                );
+            }
+        }
+        return;
+    }
+
+    if let Some(branch) = &report.branch {
+        println!("Switched to the new branch \"{branch}\"\n");
+    } else if options.branch.is_some() {
+        println!("Not inside a git repository, skipping the branch creation.\n");
+    }
+
+    for path in &report.written_files {
+        println!("Wrote {}", path.display());
+    }
+    println!();
+
+    print!("{}", format_applied_updates(&report.updated));
+
+    for message in &report.commits {
+        println!("\nCommitted \"{message}\"");
+    }
+    if options.commit && report.commits.is_empty() {
+        println!("\nNot inside a git repository, skipping the commit.");
+    }
+
+    if let Some(output) = &report.check {
+        println!("\nExecuted {}:", "cargo check".bold());
+        // Written as is, keeping the colors cargo used
+        let _ = std::io::stdout().write_all(&output.stdout);
+        let _ = std::io::stderr().write_all(&output.stderr);
+        if !output.status.success() {
+            println!("{}", "cargo check failed".red());
+        }
+    }
+}
+
+/// Updates written to the Cargo.toml files, grouped by kind of dependencies
+fn format_applied_updates(updates: &[AppliedUpdate]) -> String {
+    let mut summary = format!(
+        "Updated {} dependencies:\n",
+        updates.len().to_string().bold()
+    );
+
+    for kind in DependencyKind::ordered() {
+        let mut updates = updates.iter().filter(|u| u.kind == kind).peekable();
+
+        if updates.peek().is_none() {
+            continue;
+        }
+
+        summary.push_str(&format!(
+            "\n{}:\n",
+            get_dependencies_subsection_title(kind).cyan()
+        ));
+
+        for update in updates {
+            summary.push_str(&format!(
+                "  {}: {} -> {} ({})\n",
+                update.name,
+                update.requirement,
+                update.new_requirement,
+                update.manifest_path.display()
+            ));
+        }
+    }
+
+    summary
+}
+
+pub fn format_skipped(skipped: &SkippedDependencies) -> Option<String> {
+    let reasons = [
+        ("excluded", &skipped.excluded),
+        ("without a version", &skipped.without_version),
+        (
+            "with an unsupported requirement",
+            &skipped.unsupported_requirement,
+        ),
+        ("from another registry", &skipped.other_registry),
+        ("from [patch.crates-io]", &skipped.patched),
+    ]
+    .into_iter()
+    .filter(|(_, names)| !names.is_empty())
+    .map(|(reason, names)| format!("{} {reason} ({})", names.len(), names.join(", ")))
+    .collect::<Vec<_>>();
+
+    (!reasons.is_empty()).then(|| format!("Skipped {}.", reasons.join(", ")))
+}
+
+/// Warning about the major updates requiring a newer Rust version than the `rust-version` of their
+/// package, to be confirmed before applying them, e.g. with `--ignore-rust-version`
+pub fn format_rust_version_warnings<'a>(
+    dependencies: impl IntoIterator<Item = &'a Dependency>,
+) -> Option<String> {
+    let warnings = dependencies
+        .into_iter()
+        .filter(|dependency| dependency.is_major_update())
+        .filter_map(|dependency| {
+            let latest_rust_version = dependency.latest_rust_version.as_deref()?;
+            let rust_version = dependency.rust_version.as_deref()?;
+            let package = dependency
+                .package_name
+                .as_deref()
+                .filter(|_| dependency.workspace_path.is_some())
+                .unwrap_or("the package");
+            Some(format!(
+                "  {} {} requires Rust {latest_rust_version} but {package} declares {rust_version}",
+                dependency.name.as_str().bold(),
+                dependency.latest_version
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    (!warnings.is_empty()).then(|| {
+        format!(
+            "{}\n{}",
+            format!(
+                "{} major updates require a newer Rust version:",
+                warnings.len()
+            )
+            .magenta()
+            .bold(),
+            warnings.join("\n")
+        )
+    })
+}
+
+/// Number of dependencies of each kind, e.g. `(8 normal, 3 dev, 1 build)`, skipping the kinds
+/// without any
+pub fn format_kind_counts(dependencies: &Dependencies) -> String {
+    let counts = DependencyKind::ordered()
+        .into_iter()
+        .filter_map(|kind| {
+            let count = dependencies.iter().filter(|d| d.kind == kind).count();
+            (count > 0).then(|| format!("{count} {}", get_kind_name(kind)))
+        })
+        .collect::<Vec<_>>();
+
+    format!("({})", counts.join(", "))
+}
+
+fn is_crate_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+/// Index of the first dependency whose name starts with the prefix, ignoring case
+fn find_by_name_prefix<'a>(
+    dependencies: impl IntoIterator<Item = (usize, &'a Dependency)>,
+    prefix: &str,
+) -> Option<usize> {
+    let prefix = prefix.to_ascii_lowercase();
+
+    dependencies
+        .into_iter()
+        .find(|(_, dep)| dep.name.to_ascii_lowercase().starts_with(&prefix))
+        .map(|(i, _)| i)
+}
+
+/// Index of the next (or previous) unselected dependency from the cursor, wrapping around
+fn find_unselected(selected: &[bool], cursor_location: usize, forward: bool) -> Option<usize> {
+    let len = selected.len();
+
+    (1..=len)
+        .map(|step| {
+            if forward {
+                (cursor_location + step) % len
+            } else {
+                (cursor_location + len * step - step) % len
+            }
+        })
+        .find(|&i| !selected[i])
+}
+
+fn get_days_since_epoch_today() -> Option<i64> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?;
+    Some((now.as_secs() / 86_400) as i64)
+}
+
+/// Number of days since 1970-01-01 of a `YYYY-MM-DD` date, based on
+/// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn get_days_since_epoch(date: &str) -> Option<i64> {
+    let mut parts = date.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    Some(era * 146_097 + day_of_era - 719_468)
+}
+
+fn get_relative_date_from_datetime_string(
+    datetime_string: Option<&str>,
+    today: i64,
+) -> Option<String> {
+    let days = today - get_days_since_epoch(get_date_from_datetime_string(datetime_string)?)?;
+
+    Some(match days {
+        ..=0 => "today".to_string(),
+        1..31 => format!("{days}d ago"),
+        31..365 => format!("{}mo ago", days / 30),
+        _ => format!("{}y ago", days / 365),
+    })
+}
+
+fn format_downloads(downloads: u64) -> String {
+    match downloads {
+        0..1_000 => downloads.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", downloads as f64 / 1_000.0),
+        _ => format!("{:.1}M", downloads as f64 / 1_000_000.0),
+    }
+}
+
+fn truncate_to_width(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+
+    if width == 0 {
+        return String::new();
+    }
+
+    let mut truncated = text.chars().take(width - 1).collect::<String>();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_longest_attributes() {
+        let dependencies = Dependencies::new(
+            vec![
+                Dependency {
+                    name: "short".to_string(),
+                    requirement: "1".to_string(),
+                    current_version: "1".to_string(),
+                    latest_version: "2".to_string(),
+                    ..Default::default()
                 },
                 Dependency {
                     name: "longer dependency name".to_string(),
+                    requirement: "^1.2.11".to_string(),
                     current_version: "1.2.11".to_string(),
                     latest_version: "2.3.4".to_string(),
                     package_name: Some("package_name".to_string()),
@@ -328,11 +1916,341 @@ mod tests {
         );
         let longest = Longest::get_longest_attributes(&dependencies);
         assert_eq!(longest.name, 22);
-        assert_eq!(longest.current_version, 6);
+        assert_eq!(longest.requirement, 7);
         assert_eq!(longest.latest_version, 5);
         assert_eq!(longest.package_name, 12);
     }
 
+    #[test]
+    fn test_format_column_headers() {
+        let longest = Longest {
+            name: 6,
+            requirement: 5,
+            latest_version: 5,
+            package_name: 8,
+        };
+
+        assert_eq!(
+            format_column_headers(&longest, false, false, false),
+            "  name    current (date)   -> latest (date)      repository - description"
+        );
+        assert_eq!(
+            format_column_headers(&longest, true, true, true),
+            "  name    package   current (date)   -> latest (date)      recent  rel.  repository - description"
+        );
+    }
+
+    #[test]
+    fn test_format_up_to_date_dependency() {
+        let dependency = Dependency {
+            name: "serde".to_string(),
+            requirement: "1.0".to_string(),
+            repository: Some("https://github.com/serde-rs/serde".to_string()),
+            description: Some("A serialization framework".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            format_up_to_date_dependency(&dependency, 6, "2024-01-01", usize::MAX),
+            "  serde   2024-01-01 1.0  https://github.com/serde-rs/serde - A serialization framework"
+        );
+        assert_eq!(
+            format_up_to_date_dependency(&dependency, 5, "2024-01-01", 65),
+            "  serde  2024-01-01 1.0  https://github.com/serde-rs/serde - A s…"
+        );
+    }
+
+    #[test]
+    fn test_preselect_update_type() {
+        let dependency = |name: &str, latest_version: &str, advisories: &[&str]| Dependency {
+            name: name.to_string(),
+            current_version: "1.0.0".to_string(),
+            latest_version: latest_version.to_string(),
+            advisories: advisories.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        };
+
+        let state = State::new(
+            Dependencies::new(
+                vec![
+                    dependency("a", "1.0.1", &[]),
+                    dependency("b", "1.1.0", &[]),
+                    dependency("c", "2.0.0", &["RUSTSEC-2023-0001"]),
+                    dependency("d", "1.0.2", &[]),
+                ],
+                Default::default(),
+            ),
+            4,
+            |dependency| dependency.update_type() == Some(UpdateType::Patch),
+            &HashSet::new(),
+            vec![],
+            0,
+            vec![],
+        );
+        assert_eq!(state.selected_names(), vec!["a", "c", "d"]);
+    }
+
+    #[test]
+    fn test_refresh() {
+        let dependency = |name: &str, latest_version: &str| Dependency {
+            name: name.to_string(),
+            current_version: "1.0.0".to_string(),
+            latest_version: latest_version.to_string(),
+            ..Default::default()
+        };
+        let result =
+            |outdated_deps: Vec<Dependency>, failed_deps: Vec<(String, String)>| ScanResult {
+                total_deps: 3,
+                outdated_deps: Dependencies::new(outdated_deps, Default::default()),
+                up_to_date_deps: vec![],
+                ahead_deps: vec![],
+                unchecked_deps: 0,
+                failed_deps,
+                transitive_deps: vec![],
+                skipped_deps: Default::default(),
+                crates_io_replacement: None,
+                config: Default::default(),
+            };
+
+        let mut state = State::new(
+            Dependencies::new(vec![dependency("toml", "1.1.0")], Default::default()),
+            3,
+            |_| false,
+            &HashSet::from_iter(["toml".to_string()]),
+            vec![],
+            0,
+            vec![],
+        );
+        state.set_failed_deps(vec![
+            ("serde".to_string(), "timeout".to_string()),
+            ("semver".to_string(), "timeout".to_string()),
+        ]);
+        assert_eq!(state.failed_names(), vec!["serde", "semver"]);
+
+        state.refresh_failed(result(
+            vec![dependency("serde", "2.0.0")],
+            vec![("semver".to_string(), "timeout".to_string())],
+        ));
+        assert_eq!(state.failed_names(), vec!["semver"]);
+        assert_eq!(state.selected_names(), vec!["toml"]);
+        assert_eq!(state.outdated_deps.len(), 2);
+        assert_eq!(state.update_type_counts.major, 1);
+        assert_eq!(state.longest_attributes.latest_version, 5);
+
+        state.refresh_all(result(
+            vec![dependency("semver", "1.0.1"), dependency("toml", "1.2.0")],
+            vec![],
+        ));
+        assert!(state.failed_names().is_empty());
+        assert_eq!(state.selected_names(), vec!["toml"]);
+        assert_eq!(state.outdated_deps.len(), 2);
+        assert_eq!(state.update_type_counts.major, 0);
+    }
+
+    #[test]
+    fn test_refresh_all_same_dependency_in_several_sections() {
+        let dependency = |kind| Dependency {
+            name: "serde".to_string(),
+            current_version: "1.0.0".to_string(),
+            latest_version: "1.0.210".to_string(),
+            kind,
+            ..Default::default()
+        };
+        let dependencies = || {
+            Dependencies::new(
+                vec![
+                    dependency(DependencyKind::Normal),
+                    dependency(DependencyKind::Dev),
+                ],
+                Default::default(),
+            )
+        };
+
+        let mut state = State::new(
+            dependencies(),
+            2,
+            |_| false,
+            &HashSet::new(),
+            vec![],
+            0,
+            vec![],
+        );
+        state.selected[1] = true;
+        state.refresh_all(ScanResult {
+            total_deps: 2,
+            outdated_deps: dependencies(),
+            up_to_date_deps: vec![],
+            ahead_deps: vec![],
+            unchecked_deps: 0,
+            failed_deps: vec![],
+            transitive_deps: vec![],
+            skipped_deps: Default::default(),
+            crates_io_replacement: None,
+            config: Default::default(),
+        });
+
+        let selected = state.selected_dependencies();
+        assert_eq!(selected.len(), 1);
+        assert!(selected.iter().all(|d| d.kind == DependencyKind::Dev));
+    }
+
+    #[test]
+    fn test_toggle_selected_only() {
+        let dependency = |name: &str| Dependency {
+            name: name.to_string(),
+            current_version: "1.0.0".to_string(),
+            latest_version: "1.1.0".to_string(),
+            ..Default::default()
+        };
+        let mut state = State::new(
+            Dependencies::new(
+                vec![
+                    dependency("a"),
+                    dependency("b"),
+                    dependency("c"),
+                    dependency("d"),
+                ],
+                Default::default(),
+            ),
+            4,
+            |_| false,
+            &HashSet::new(),
+            vec![],
+            0,
+            vec![],
+        );
+
+        state.toggle_selected_only();
+        assert!(!state.show_selected_only);
+        assert!(state.status.is_some());
+
+        state.selected[1] = true;
+        state.selected[3] = true;
+        state.toggle_selected_only();
+        assert!(state.show_selected_only);
+        assert_eq!(state.visible_indices(), vec![1, 3]);
+        assert_eq!(state.cursor_location, 1);
+
+        state.cursor_location = 3;
+        state.selected[3] = false;
+        state.keep_cursor_visible();
+        assert_eq!(state.cursor_location, 1);
+
+        state.toggle_selected_only();
+        assert!(!state.show_selected_only);
+        assert_eq!(state.cursor_location, 1);
+        assert_eq!(state.visible_indices(), vec![0, 1, 2, 3]);
+
+        state.toggle_selected_only();
+        state.selected[1] = false;
+        state.keep_cursor_visible();
+        assert!(!state.show_selected_only);
+    }
+
+    #[test]
+    fn test_update_type_counts() {
+        let dependency = |current_version: &str, latest_version: &str| Dependency {
+            current_version: current_version.to_string(),
+            latest_version: latest_version.to_string(),
+            ..Default::default()
+        };
+        let dependencies = Dependencies::new(
+            vec![
+                dependency("1.0.0", "1.0.1"),
+                dependency("1.0.0", "1.1.0"),
+                dependency("0.1.0", "0.2.0"),
+                dependency("1.0.0", "2.0.0"),
+            ],
+            std::collections::HashMap::new(),
+        );
+        assert_eq!(
+            UpdateTypeCounts::count(dependencies.iter()),
+            UpdateTypeCounts {
+                patch: 1,
+                minor: 1,
+                major: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_format_selection() {
+        assert_eq!(
+            format_selection(0, &UpdateTypeCounts::default()),
+            "0 selected"
+        );
+        assert_eq!(
+            format_selection(
+                7,
+                &UpdateTypeCounts {
+                    patch: 2,
+                    minor: 3,
+                    major: 2
+                }
+            ),
+            "7 selected: 2 major, 3 minor, 2 patch"
+        );
+        assert_eq!(
+            format_selection(
+                3,
+                &UpdateTypeCounts {
+                    patch: 2,
+                    minor: 0,
+                    major: 1
+                }
+            ),
+            "3 selected: 1 major, 2 patch"
+        );
+    }
+
+    #[test]
+    fn test_format_intermediate_versions() {
+        let dependency = |intermediate_versions: &[&str], yanked_versions: &[&str]| Dependency {
+            intermediate_versions: intermediate_versions
+                .iter()
+                .map(|v| v.to_string())
+                .collect(),
+            yanked_versions: yanked_versions.iter().map(|v| v.to_string()).collect(),
+            ..Default::default()
+        };
+
+        let dependency_with_yanked = dependency(&["0.2.0", "0.10.0"], &["0.3.0"]);
+        assert_eq!(
+            format_intermediate_versions(&dependency_with_yanked, false),
+            "0.2.0, 0.10.0"
+        );
+        assert_eq!(
+            format_intermediate_versions(&dependency_with_yanked, true),
+            format!("0.2.0, {}, 0.10.0", "0.3.0".crossed_out())
+        );
+        assert_eq!(
+            format_intermediate_versions(&dependency(&[], &["0.3.0"]), false),
+            "none"
+        );
+        assert_eq!(
+            format_intermediate_versions(&dependency(&[], &[]), true),
+            "none"
+        );
+    }
+
+    #[test]
+    fn test_get_relative_path() {
+        assert_eq!(
+            get_relative_path(
+                Path::new("/home/user/project/member/Cargo.toml"),
+                Path::new("/home/user/project")
+            ),
+            Path::new("member/Cargo.toml")
+        );
+        assert_eq!(
+            get_relative_path(
+                Path::new("/home/user/project/Cargo.toml"),
+                Path::new("/home/user/project/member/src")
+            ),
+            Path::new("../../Cargo.toml")
+        );
+    }
+
     #[test]
     fn test_get_date_from_datetime_string() {
         assert_eq!(
@@ -346,6 +2264,110 @@ mod tests {
         assert_eq!(get_date_from_datetime_string(None), None);
     }
 
+    #[test]
+    fn test_format_transitive_dependency() {
+        let dependency = Dependency {
+            name: "syn".to_string(),
+            current_version: "1.0.0".to_string(),
+            latest_version: "2.0.0".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_transitive_dependency(&dependency, 5),
+            "  syn    1.0.0 -> 2.0.0"
+        );
+
+        let dependency = Dependency {
+            advisories: vec!["RUSTSEC-2023-0001".to_string()],
+            ..dependency
+        };
+        assert_eq!(
+            format_transitive_dependency(&dependency, 3),
+            "  syn  1.0.0 -> 2.0.0  ⚠ RUSTSEC-2023-0001"
+        );
+    }
+
+    #[test]
+    fn test_format_list_line() {
+        let dependency = Dependency {
+            name: "serde".to_string(),
+            requirement: "1".to_string(),
+            current_version: "1.0.0".to_string(),
+            latest_version: "1.0.210".to_string(),
+            kind: DependencyKind::Dev,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            format_list_line(&dependency),
+            "serde 1.0.0 -> 1.0.210 (dev)"
+        );
+    }
+
+    #[test]
+    fn test_format_add_command() {
+        let mut dependency = Dependency {
+            name: "serde".to_string(),
+            requirement: "1".to_string(),
+            current_version: "1.0.0".to_string(),
+            latest_version: "1.0.210".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_add_command(&dependency).as_deref(),
+            Some("cargo add serde@1.0.210")
+        );
+
+        dependency.kind = DependencyKind::Dev;
+        dependency.operator = Some(VersionOperator::Exact);
+        dependency.package_name = Some("member".to_string());
+        dependency.workspace_path = Some("member".to_string());
+        assert_eq!(
+            format_add_command(&dependency).as_deref(),
+            Some("cargo add serde@=1.0.210 --dev -p member")
+        );
+
+        dependency.kind = DependencyKind::Workspace;
+        assert_eq!(format_add_command(&dependency), None);
+    }
+
+    #[test]
+    fn test_format_markdown_table() {
+        let dependencies = Dependencies::new(
+            vec![
+                Dependency {
+                    name: "serde".to_string(),
+                    current_version: "1.0.0".to_string(),
+                    latest_version: "1.0.210".to_string(),
+                    repository: Some("https://github.com/serde-rs/serde".to_string()),
+                    ..Default::default()
+                },
+                Dependency {
+                    name: "insta".to_string(),
+                    current_version: "1.39.0".to_string(),
+                    latest_version: "1.40.0".to_string(),
+                    kind: DependencyKind::Dev,
+                    ..Default::default()
+                },
+            ],
+            Default::default(),
+        );
+
+        assert_eq!(
+            format_markdown_table(&dependencies),
+            concat!(
+                "| Crate | Current | Latest | Kind | Repository |\n",
+                "| --- | --- | --- | --- | --- |\n",
+                "| serde | 1.0.0 | 1.0.210 | normal | [https://github.com/serde-rs/serde](https://github.com/serde-rs/serde) |\n",
+                "| insta | 1.39.0 | 1.40.0 | dev | - |\n",
+            )
+        );
+        assert_eq!(
+            format_markdown_table(&Dependencies::default()),
+            "| Crate | Current | Latest | Kind | Repository |\n| --- | --- | --- | --- | --- |\n"
+        );
+    }
+
     #[test]
     fn test_get_dependencies_subsection_title() {
         assert_eq!(
@@ -365,4 +2387,369 @@ mod tests {
             "Workspace dependencies"
         );
     }
+
+    #[test]
+    fn test_format_applied_updates() {
+        let updates = [
+            AppliedUpdate {
+                name: "toml".to_string(),
+                kind: DependencyKind::Dev,
+                requirement: "0.7".to_string(),
+                new_requirement: "=0.8.0".to_string(),
+                manifest_path: PathBuf::from("project/member/Cargo.toml"),
+            },
+            AppliedUpdate {
+                name: "serde".to_string(),
+                kind: DependencyKind::Normal,
+                requirement: "1.0.0".to_string(),
+                new_requirement: "=1.0.1".to_string(),
+                manifest_path: PathBuf::from("project/Cargo.toml"),
+            },
+        ];
+
+        let summary = format_applied_updates(&updates);
+        assert!(summary.starts_with(&format!("Updated {} dependencies", "2".bold())));
+        assert!(summary.contains("serde: 1.0.0 -> =1.0.1 (project/Cargo.toml)"));
+        assert!(summary.contains("toml: 0.7 -> =0.8.0 (project/member/Cargo.toml)"));
+        assert!(summary.find("serde").unwrap() < summary.find("toml").unwrap());
+
+        let summary = format_applied_updates(&updates[..1]);
+        assert!(summary.starts_with(&format!("Updated {} dependencies", "1".bold())));
+        assert!(!summary.contains("serde"));
+    }
+
+    #[test]
+    fn test_format_key_reference() {
+        let lines = format_key_reference(SortOrder::default());
+        assert_eq!(lines.len(), 21);
+        assert!(lines.iter().any(|line| line.ends_with(&format!(
+            "change the sort order ({})",
+            SortOrder::default().label()
+        ))));
+        assert!(lines
+            .iter()
+            .any(|line| line.contains(&format!("{}  exit", "<esc>/<q>    ".cyan()))));
+    }
+
+    #[test]
+    fn test_format_skipped() {
+        assert_eq!(format_skipped(&SkippedDependencies::default()), None);
+        assert_eq!(
+            format_skipped(&SkippedDependencies {
+                excluded: vec!["bar".to_string(), "foo".to_string()],
+                without_version: vec!["local".to_string()],
+                unsupported_requirement: vec![],
+                other_registry: vec!["internal".to_string()],
+                patched: vec!["serde".to_string()],
+            }),
+            Some(
+                "Skipped 2 excluded (bar, foo), 1 without a version (local), 1 from another registry (internal), 1 from [patch.crates-io] (serde)."
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_exceeds_changes_limit() {
+        let dependency = |latest_version: &str| Dependency {
+            current_version: "1.0.0".to_string(),
+            latest_version: latest_version.to_string(),
+            ..Default::default()
+        };
+        let dependencies = |latest_versions: &[&str]| {
+            Dependencies::new(
+                latest_versions.iter().map(|v| dependency(v)).collect(),
+                Default::default(),
+            )
+        };
+
+        assert!(!exceeds_changes_limit(
+            &dependencies(&["1.1.0", "1.0.1"]),
+            2
+        ));
+        assert!(exceeds_changes_limit(
+            &dependencies(&["1.1.0", "1.0.1", "1.2.0"]),
+            2
+        ));
+        assert!(exceeds_changes_limit(&dependencies(&["2.0.0"]), 2));
+    }
+
+    #[test]
+    fn test_format_rust_version_warnings() {
+        let dependency =
+            |name: &str, latest_version: &str, latest_rust_version: Option<&str>| Dependency {
+                name: name.to_string(),
+                current_version: "1.0.0".to_string(),
+                latest_version: latest_version.to_string(),
+                latest_rust_version: latest_rust_version.map(str::to_string),
+                rust_version: Some("1.75.0".to_string()),
+                ..Default::default()
+            };
+
+        let dependencies = [
+            dependency("foo", "2.0.0", Some("1.80")),
+            dependency("bar", "1.5.0", Some("1.80")),
+            dependency("baz", "2.0.0", None),
+            Dependency {
+                package_name: Some("member".to_string()),
+                workspace_path: Some("crates/member".to_string()),
+                ..dependency("qux", "3.0.0", Some("1.81"))
+            },
+        ];
+        let warnings = format_rust_version_warnings(&dependencies).unwrap();
+        assert!(warnings.contains("2 major updates require a newer Rust version:"));
+        assert!(warnings.contains("2.0.0 requires Rust 1.80 but the package declares 1.75.0"));
+        assert!(warnings.contains("3.0.0 requires Rust 1.81 but member declares 1.75.0"));
+        assert!(!warnings.contains("bar") && !warnings.contains("baz"));
+
+        assert_eq!(format_rust_version_warnings(&[]), None);
+    }
+
+    #[test]
+    fn test_format_kind_counts() {
+        let dependency = |kind| Dependency {
+            kind,
+            ..Default::default()
+        };
+        let dependencies = Dependencies::new(
+            vec![
+                dependency(DependencyKind::Normal),
+                dependency(DependencyKind::Build),
+                dependency(DependencyKind::Normal),
+                dependency(DependencyKind::Dev),
+            ],
+            Default::default(),
+        );
+
+        assert_eq!(
+            format_kind_counts(&dependencies),
+            "(2 normal, 1 dev, 1 build)"
+        );
+    }
+
+    #[test]
+    fn test_find_by_name_prefix() {
+        let dependency = |name: &str| Dependency {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let dependencies = Dependencies::new(
+            vec![
+                dependency("serde"),
+                dependency("serde_json"),
+                dependency("tokio"),
+            ],
+            Default::default(),
+        );
+
+        let all = || dependencies.iter().enumerate();
+        assert_eq!(find_by_name_prefix(all(), "s"), Some(0));
+        assert_eq!(find_by_name_prefix(all(), "serde_"), Some(1));
+        assert_eq!(find_by_name_prefix(all(), "TOK"), Some(2));
+        assert_eq!(find_by_name_prefix(all(), "x"), None);
+        assert_eq!(find_by_name_prefix(all().skip(1), "s"), Some(1));
+    }
+
+    #[test]
+    fn test_jump_after_slash() {
+        let dependency = |name: &str| Dependency {
+            name: name.to_string(),
+            current_version: "1.0.0".to_string(),
+            latest_version: "1.1.0".to_string(),
+            ..Default::default()
+        };
+        let mut state = State::new(
+            Dependencies::new(
+                vec![dependency("serde"), dependency("syn"), dependency("tokio")],
+                Default::default(),
+            ),
+            3,
+            |_| false,
+            &HashSet::new(),
+            vec![],
+            0,
+            vec![],
+        );
+        let press = |state: &mut State, code| {
+            state
+                .handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+                .unwrap()
+        };
+
+        // Keys bound to actions extend the name being typed, <enter> only ends the jump
+        press(&mut state, KeyCode::Char('/'));
+        press(&mut state, KeyCode::Char('s'));
+        press(&mut state, KeyCode::Char('y'));
+        assert!(matches!(
+            press(&mut state, KeyCode::Enter),
+            Event::HandleKeyboard
+        ));
+        assert_eq!(state.cursor_location, 1);
+        assert_eq!(state.sort_order, SortOrder::Name);
+
+        press(&mut state, KeyCode::Char('/'));
+        press(&mut state, KeyCode::Char('t'));
+        assert_eq!(state.cursor_location, 2);
+        press(&mut state, KeyCode::Backspace);
+        press(&mut state, KeyCode::Char('s'));
+        assert_eq!(state.cursor_location, 0);
+
+        // Any other key ends the jump and does as usual
+        press(&mut state, KeyCode::Down);
+        assert_eq!(state.cursor_location, 1);
+        press(&mut state, KeyCode::Char('s'));
+        assert_eq!(state.sort_order, SortOrder::Age);
+        assert!(!state.jumping);
+    }
+
+    #[test]
+    fn test_refresh_keys() {
+        let mut state = State::new(
+            Dependencies::new(
+                vec![Dependency {
+                    name: "serde".to_string(),
+                    current_version: "1.0.0".to_string(),
+                    latest_version: "1.1.0".to_string(),
+                    ..Default::default()
+                }],
+                Default::default(),
+            ),
+            1,
+            |_| false,
+            &HashSet::new(),
+            vec![],
+            0,
+            vec![],
+        );
+        let press = |state: &mut State, code, modifiers| {
+            state.handle_key(KeyEvent::new(code, modifiers)).unwrap()
+        };
+
+        assert!(matches!(
+            press(&mut state, KeyCode::Char('r'), KeyModifiers::NONE),
+            Event::RefreshFailed
+        ));
+        assert!(matches!(
+            press(&mut state, KeyCode::Char('R'), KeyModifiers::SHIFT),
+            Event::RefreshAll
+        ));
+
+        press(&mut state, KeyCode::Char('b'), KeyModifiers::NONE);
+        assert!(state.show_releases_behind);
+    }
+
+    #[test]
+    fn test_confirm_update_requiring_newer_rust() {
+        let mut state = State::new(
+            Dependencies::new(
+                vec![Dependency {
+                    name: "serde".to_string(),
+                    current_version: "1.0.0".to_string(),
+                    latest_version: "2.0.0".to_string(),
+                    latest_rust_version: Some("1.80".to_string()),
+                    rust_version: Some("1.75.0".to_string()),
+                    ..Default::default()
+                }],
+                Default::default(),
+            ),
+            1,
+            |_| true,
+            &HashSet::new(),
+            vec![],
+            0,
+            vec![],
+        );
+        let press = |state: &mut State, code| {
+            state
+                .handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+                .unwrap()
+        };
+
+        // The warning is shown before updating, any other key going back
+        assert!(matches!(
+            press(&mut state, KeyCode::Enter),
+            Event::HandleKeyboard
+        ));
+        assert!(state
+            .status
+            .as_deref()
+            .is_some_and(|status| status.contains("requires Rust 1.80 but the package declares")));
+        press(&mut state, KeyCode::Char('d'));
+        assert!(state.show_downloads);
+        assert!(state.status.is_none());
+
+        press(&mut state, KeyCode::Enter);
+        assert!(matches!(
+            press(&mut state, KeyCode::Enter),
+            Event::UpdateDependencies
+        ));
+    }
+
+    #[test]
+    fn test_find_unselected() {
+        let selected = [true, false, true, true, false];
+        assert_eq!(find_unselected(&selected, 0, true), Some(1));
+        assert_eq!(find_unselected(&selected, 1, true), Some(4));
+        assert_eq!(find_unselected(&selected, 4, true), Some(1));
+        assert_eq!(find_unselected(&selected, 0, false), Some(4));
+        assert_eq!(find_unselected(&selected, 4, false), Some(1));
+        assert_eq!(find_unselected(&selected, 1, false), Some(4));
+        assert_eq!(find_unselected(&[true, true], 0, true), None);
+        assert_eq!(find_unselected(&[false], 0, true), Some(0));
+    }
+
+    #[test]
+    fn test_get_days_since_epoch() {
+        assert_eq!(get_days_since_epoch("1970-01-01"), Some(0));
+        assert_eq!(get_days_since_epoch("2000-03-01"), Some(11_017));
+        assert_eq!(get_days_since_epoch("2024-01-01"), Some(19_723));
+        assert_eq!(get_days_since_epoch("2024-13-01"), None);
+        assert_eq!(get_days_since_epoch("2024-01"), None);
+        assert_eq!(get_days_since_epoch("not a date"), None);
+    }
+
+    #[test]
+    fn test_get_relative_date_from_datetime_string() {
+        let today = get_days_since_epoch("2024-06-01").unwrap();
+        let relative_date =
+            |datetime| get_relative_date_from_datetime_string(Some(datetime), today);
+
+        assert_eq!(
+            relative_date("2024-06-01T10:00:00Z"),
+            Some("today".to_string())
+        );
+        assert_eq!(
+            relative_date("2024-05-29T10:00:00Z"),
+            Some("3d ago".to_string())
+        );
+        assert_eq!(
+            relative_date("2024-02-01T10:00:00Z"),
+            Some("4mo ago".to_string())
+        );
+        assert_eq!(
+            relative_date("2021-05-01T10:00:00Z"),
+            Some("3y ago".to_string())
+        );
+        assert_eq!(relative_date("2024-06-01"), None);
+        assert_eq!(get_relative_date_from_datetime_string(None, today), None);
+    }
+
+    #[test]
+    fn test_format_downloads() {
+        assert_eq!(format_downloads(0), "0");
+        assert_eq!(format_downloads(999), "999");
+        assert_eq!(format_downloads(1_000), "1.0k");
+        assert_eq!(format_downloads(123_456), "123.5k");
+        assert_eq!(format_downloads(12_345_678), "12.3M");
+    }
+
+    #[test]
+    fn test_truncate_to_width() {
+        assert_eq!(truncate_to_width("A description", 20), "A description");
+        assert_eq!(truncate_to_width("A description", 13), "A description");
+        assert_eq!(truncate_to_width("A description", 12), "A descripti…");
+        assert_eq!(truncate_to_width("A description", 1), "…");
+        assert_eq!(truncate_to_width("A description", 0), "");
+        assert_eq!(truncate_to_width("Très élégant", 6), "Très …");
+    }
 }
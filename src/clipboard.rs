@@ -0,0 +1,53 @@
+use std::io::Write;
+
+/// Copies the text to the system clipboard through the terminal, with the OSC 52 escape sequence
+/// supported by most terminal emulators and by tmux, silently ignored by the others
+pub fn copy(out: &mut impl Write, text: &str) -> std::io::Result<()> {
+    write!(out, "\x1b]52;c;{}\x07", encode_base64(text.as_bytes()))?;
+    out.flush()
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, byte)| {
+            group | (u32::from(*byte) << (16 - 8 * i))
+        });
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((group >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_base64() {
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foo"), "Zm9v");
+        assert_eq!(
+            encode_base64(b"cargo add serde@1.0.210"),
+            "Y2FyZ28gYWRkIHNlcmRlQDEuMC4yMTA="
+        );
+    }
+
+    #[test]
+    fn test_copy() {
+        let mut out = vec![];
+        copy(&mut out, "foo").unwrap();
+        assert_eq!(out, b"\x1b]52;c;Zm9v\x07");
+    }
+}
@@ -0,0 +1,118 @@
+use toml_edit::{DocumentMut, Item};
+
+use crate::dependency::IgnoredVersion;
+
+/// Defaults read from the `[package.metadata.interactive-update]` or
+/// `[workspace.metadata.interactive-update]` table of the Cargo.toml file
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Config {
+    /// Selects all dependencies to be updated
+    pub all: bool,
+    /// Pin dependencies to exact versions, with an `=` prefix
+    pub pin: bool,
+    /// Don't run `cargo check` after updating
+    pub no_check: bool,
+    /// Suggest the latest versions even if they require a newer Rust version
+    pub ignore_rust_version: bool,
+    /// Names of the dependencies to never report as outdated
+    pub exclude: Vec<String>,
+    /// Versions of the dependencies to never suggest, the invalid ones being left out
+    pub ignore: Vec<IgnoredVersion>,
+}
+
+impl Config {
+    pub fn from_cargo_toml(cargo_toml: &DocumentMut) -> Self {
+        let Some(table) = ["package", "workspace"].iter().find_map(|section| {
+            cargo_toml
+                .get(section)?
+                .get("metadata")?
+                .get("interactive-update")
+        }) else {
+            return Self::default();
+        };
+
+        Self {
+            all: get_bool(table, "all"),
+            pin: get_bool(table, "pin"),
+            no_check: get_bool(table, "no-check"),
+            ignore_rust_version: get_bool(table, "ignore-rust-version"),
+            exclude: get_strings(table, "exclude").map(str::to_string).collect(),
+            ignore: get_strings(table, "ignore")
+                .flat_map(|s| s.parse().ok())
+                .collect(),
+        }
+    }
+}
+
+fn get_strings<'a>(table: &'a Item, key: &str) -> impl Iterator<Item = &'a str> {
+    table
+        .get(key)
+        .and_then(|i| i.as_array())
+        .into_iter()
+        .flat_map(|a| a.iter().flat_map(|v| v.as_str()))
+}
+
+fn get_bool(table: &Item, key: &str) -> bool {
+    table.get(key).and_then(|i| i.as_bool()).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_from_package_metadata() {
+        const CARGO_TOML: &str = r#"
+        [package.metadata.interactive-update]
+        pin = true
+        no-check = true
+        exclude = ["serde", 0, "toml"]
+        ignore = ["serde@1.0.211", "toml@<=0.8.0", "invalid"]
+        "#;
+
+        let cargo_toml = CARGO_TOML.parse().unwrap();
+        assert_eq!(
+            Config::from_cargo_toml(&cargo_toml),
+            Config {
+                pin: true,
+                no_check: true,
+                exclude: vec!["serde".to_string(), "toml".to_string()],
+                ignore: vec![
+                    "serde@1.0.211".parse().unwrap(),
+                    "toml@<=0.8.0".parse().unwrap(),
+                ],
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_config_from_workspace_metadata() {
+        const CARGO_TOML: &str = r#"
+        [workspace.metadata.interactive-update]
+        all = true
+        ignore-rust-version = true
+        "#;
+
+        let cargo_toml = CARGO_TOML.parse().unwrap();
+        assert_eq!(
+            Config::from_cargo_toml(&cargo_toml),
+            Config {
+                all: true,
+                ignore_rust_version: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_config_with_no_metadata() {
+        const CARGO_TOML: &str = r#"
+        [package]
+        name = "cargo-outdated"
+        "#;
+
+        let cargo_toml = CARGO_TOML.parse().unwrap();
+        assert_eq!(Config::from_cargo_toml(&cargo_toml), Config::default());
+    }
+}
@@ -1,33 +1,230 @@
-use crossterm::style::Stylize;
-use std::collections::{HashMap, HashSet};
+use semver::{Version, VersionReq};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
 use toml_edit::{value, DocumentMut, Item, Value};
 
-use crate::args::Args;
+use crate::{AppliedUpdate, ApplyOptions, ApplyReport, Error};
 
 #[derive(Clone, PartialEq, Eq, Default)]
 pub struct Dependency {
     pub name: String,
+    /// Version requirement as written in the Cargo.toml file, e.g. `^1.2`
+    pub requirement: String,
+    /// Lowest version matching the requirement, e.g. `1.2.0`
     pub current_version: String,
     pub latest_version: String,
     pub repository: Option<String>,
     pub description: Option<String>,
     pub latest_version_date: Option<String>,
     pub current_version_date: Option<String>,
+    pub downloads: Option<u64>,
+    pub recent_downloads: Option<u64>,
+    /// Stable versions released between the current and the latest one, oldest first
+    pub intermediate_versions: Vec<String>,
+    /// Stable versions released between the current and the latest one that were yanked, never
+    /// suggested, oldest first
+    pub yanked_versions: Vec<String>,
+    /// `rust-version` required by the latest version, only when newer than the one declared by
+    /// the package, e.g. with `--ignore-rust-version`
+    pub latest_rust_version: Option<String>,
+    /// `rust-version` declared by the package, or by the workspace it inherits it from
+    pub rust_version: Option<String>,
+    /// Whether the current version was yanked
+    pub current_version_yanked: bool,
+    pub operator: Option<VersionOperator>,
+    /// Security advisories affecting the current version
+    pub advisories: Vec<String>,
     pub kind: DependencyKind,
+    /// Overridden by an entry of the `[patch.crates-io]` table of the root, from a git repository
+    /// or a path, which decides the version used whatever the requirement
+    pub patched: bool,
     pub package_name: Option<String>,
     pub workspace_path: Option<String>,
 }
 
-impl Ord for Dependency {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        let ordering = self.kind.cmp(&other.kind);
+/// How breaking an update is, following Cargo's semver compatibility rules where the left-most
+/// non-zero component is the major one
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+pub enum UpdateType {
+    Patch,
+    Minor,
+    Major,
+}
 
-        if ordering == std::cmp::Ordering::Equal {
-            self.name.cmp(&other.name)
-        } else {
-            ordering
+/// Operator written in front of the new version requirement
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VersionOperator {
+    Bare,
+    Caret,
+    Tilde,
+    Exact,
+}
+
+impl VersionOperator {
+    pub const fn prefix(self) -> &'static str {
+        match self {
+            VersionOperator::Bare => "",
+            VersionOperator::Caret => "^",
+            VersionOperator::Tilde => "~",
+            VersionOperator::Exact => "=",
         }
     }
+
+    /// Next operator when cycling through them, `None` being the default one
+    pub const fn cycle(operator: Option<Self>) -> Option<Self> {
+        match operator {
+            None => Some(VersionOperator::Caret),
+            Some(VersionOperator::Caret) => Some(VersionOperator::Tilde),
+            Some(VersionOperator::Tilde) => Some(VersionOperator::Exact),
+            Some(VersionOperator::Exact) => Some(VersionOperator::Bare),
+            Some(VersionOperator::Bare) => None,
+        }
+    }
+}
+
+impl Dependency {
+    /// Update of the dependency to the given requirement, in the Cargo.toml file declaring it
+    fn applied_update(&self, manifest_dir: &Path, new_requirement: String) -> AppliedUpdate {
+        AppliedUpdate {
+            name: self.name.clone(),
+            kind: self.kind,
+            requirement: self.requirement.clone(),
+            new_requirement,
+            manifest_path: self
+                .workspace_path
+                .as_ref()
+                .map_or(manifest_dir.to_path_buf(), |w| manifest_dir.join(w))
+                .join("Cargo.toml"),
+        }
+    }
+
+    /// Operator of the requirement as written in the Cargo.toml file
+    pub fn requirement_operator(&self) -> VersionOperator {
+        match self.requirement.trim_start().chars().next() {
+            Some('^') => VersionOperator::Caret,
+            Some('~') => VersionOperator::Tilde,
+            Some('=') => VersionOperator::Exact,
+            _ => VersionOperator::Bare,
+        }
+    }
+
+    /// Version requirement written to the Cargo.toml file, keeping the original operator (or
+    /// exact when pinning) unless another operator was chosen for this dependency
+    pub fn version_requirement(&self, pin: bool) -> String {
+        let operator = self.operator.unwrap_or(if pin {
+            VersionOperator::Exact
+        } else {
+            self.requirement_operator()
+        });
+
+        format!("{}{}", operator.prefix(), self.latest_version)
+    }
+
+    pub fn update_type(&self) -> Option<UpdateType> {
+        let current = Version::parse(&self.current_version).ok()?;
+        let latest = Version::parse(&self.latest_version).ok()?;
+
+        let is_major = if current.major != 0 || latest.major != 0 {
+            current.major != latest.major
+        } else if current.minor != 0 || latest.minor != 0 {
+            current.minor != latest.minor
+        } else {
+            current.patch != latest.patch
+        };
+
+        Some(if is_major {
+            UpdateType::Major
+        } else if current.minor != latest.minor {
+            UpdateType::Minor
+        } else {
+            UpdateType::Patch
+        })
+    }
+
+    /// Whether the crate's description marks it as deprecated or unmaintained, which usually
+    /// means a successor should be used instead of updating it
+    pub fn is_deprecated(&self) -> bool {
+        let Some(description) = &self.description else {
+            return false;
+        };
+
+        let description = description.to_lowercase();
+        ["deprecated", "unmaintained", "no longer maintained"]
+            .iter()
+            .any(|marker| description.contains(marker))
+    }
+
+    /// Number of stable releases newer than the current version, up to and including the latest
+    pub fn releases_behind(&self) -> usize {
+        let is_behind = match (
+            Version::parse(&self.current_version),
+            Version::parse(&self.latest_version),
+        ) {
+            (Ok(current), Ok(latest)) => latest > current,
+            _ => false,
+        };
+
+        self.intermediate_versions.len() + usize::from(is_behind)
+    }
+
+    /// Whether updating to the latest version is a breaking change
+    pub fn is_major_update(&self) -> bool {
+        self.update_type() == Some(UpdateType::Major)
+    }
+
+    /// Whether the latest version is already allowed by the requirement, so that only Cargo.lock
+    /// needs to be updated
+    pub fn is_compatible_update(&self) -> bool {
+        VersionReq::parse(&self.requirement)
+            .ok()
+            .zip(Version::parse(&self.latest_version).ok())
+            .is_some_and(|(requirement, version)| requirement.matches(&version))
+    }
+
+    /// Lowest version newer than the current one that wasn't yanked and still matches the
+    /// requirement, falling back to the latest version when none does
+    pub fn nearest_non_yanked_version(&self) -> &str {
+        let Ok(requirement) = VersionReq::parse(&self.requirement) else {
+            return &self.latest_version;
+        };
+
+        self.intermediate_versions
+            .iter()
+            .chain(std::iter::once(&self.latest_version))
+            .find(|version| {
+                Version::parse(version).is_ok_and(|version| requirement.matches(&version))
+            })
+            .unwrap_or(&self.latest_version)
+    }
+
+    /// Suggests the given version, released since the current one, instead of the latest one,
+    /// leaving out the versions after it and the details of the latest version which don't apply
+    fn retarget(&mut self, target: String) {
+        let target_version = Version::parse(&target).ok();
+        let is_before_target = |version: &String| {
+            Version::parse(version)
+                .ok()
+                .zip(target_version.as_ref())
+                .is_some_and(|(version, target)| &version < target)
+        };
+        self.intermediate_versions.retain(is_before_target);
+        self.yanked_versions.retain(is_before_target);
+        self.latest_version = target;
+        self.latest_version_date = None;
+        self.latest_rust_version = None;
+    }
+}
+
+impl Ord for Dependency {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // Dependencies with security advisories come first within their kind
+        self.kind
+            .cmp(&other.kind)
+            .then_with(|| self.advisories.is_empty().cmp(&other.advisories.is_empty()))
+            .then_with(|| self.name.cmp(&other.name))
+    }
 }
 
 impl PartialOrd for Dependency {
@@ -36,7 +233,126 @@ impl PartialOrd for Dependency {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+/// Timestamp of crates.io used as the date of a version
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum DateField {
+    /// When the version was last updated, e.g. yanked or unyanked
+    #[default]
+    Updated,
+    /// When the version was published
+    Created,
+}
+
+impl DateField {
+    /// Key of the timestamp in the versions returned by crates.io
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Updated => "updated_at",
+            Self::Created => "created_at",
+        }
+    }
+}
+
+/// Version of a dependency to never suggest, e.g. known to be broken, written `<NAME>@<VERSION>`
+/// or `<NAME>@<=<VERSION>` to also skip all the versions before it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IgnoredVersion {
+    pub name: String,
+    pub version: Version,
+    /// Also skip the versions before `version`
+    pub up_to: bool,
+}
+
+impl IgnoredVersion {
+    pub fn matches(&self, name: &str, version: &str) -> bool {
+        self.name == name
+            && Version::parse(version).is_ok_and(|version| {
+                if self.up_to {
+                    version <= self.version
+                } else {
+                    version == self.version
+                }
+            })
+    }
+}
+
+impl std::str::FromStr for IgnoredVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((name, version)) = s.split_once('@') else {
+            return Err(format!(
+                "expected `<NAME>@<VERSION>` or `<NAME>@<=<VERSION>`, got `{s}`"
+            ));
+        };
+        let (version, up_to) = match version.strip_prefix("<=") {
+            Some(version) => (version, true),
+            None => (version.strip_prefix('=').unwrap_or(version), false),
+        };
+        let version = Version::parse(version.trim())
+            .map_err(|e| format!("invalid version `{version}`: {e}"))?;
+
+        Ok(Self {
+            name: name.trim().to_string(),
+            version,
+            up_to,
+        })
+    }
+}
+
+/// Order of the dependencies within each kind
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum SortOrder {
+    /// By name
+    #[default]
+    Name,
+    /// Most recently released latest version first
+    Age,
+    /// Most breaking update first, major then minor then patch
+    Delta,
+    /// Most releases behind first
+    Releases,
+}
+
+impl SortOrder {
+    /// Next order when cycling through them interactively
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Name => Self::Age,
+            Self::Age => Self::Delta,
+            Self::Delta => Self::Releases,
+            Self::Releases => Self::Name,
+        }
+    }
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Age => "age",
+            Self::Delta => "delta",
+            Self::Releases => "releases",
+        }
+    }
+
+    /// Compares two dependencies, keeping them grouped by kind with the ones with security
+    /// advisories first, and falling back to the default order on ties
+    pub fn compare(self, a: &Dependency, b: &Dependency) -> std::cmp::Ordering {
+        let ordering = match self {
+            Self::Name => std::cmp::Ordering::Equal,
+            Self::Age => b.latest_version_date.cmp(&a.latest_version_date),
+            Self::Delta => b.update_type().cmp(&a.update_type()),
+            Self::Releases => b.releases_behind().cmp(&a.releases_behind()),
+        };
+
+        a.kind
+            .cmp(&b.kind)
+            .then_with(|| a.advisories.is_empty().cmp(&b.advisories.is_empty()))
+            .then(ordering)
+            .then_with(|| a.cmp(b))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub enum DependencyKind {
     #[default]
     Normal,
@@ -54,14 +370,123 @@ impl DependencyKind {
             DependencyKind::Workspace,
         ]
     }
+
+    /// Order in which the kinds are applied, the `[workspace.dependencies]` inherited by the
+    /// members coming before the members' own dependencies
+    pub const fn write_order() -> [DependencyKind; 4] {
+        [
+            DependencyKind::Workspace,
+            DependencyKind::Normal,
+            DependencyKind::Dev,
+            DependencyKind::Build,
+        ]
+    }
 }
 
-#[derive(Clone)]
+/// Message of the commit created for the updates of a kind of dependencies, with `--split-by-kind`
+fn get_commit_message(kind: DependencyKind) -> &'static str {
+    match kind {
+        DependencyKind::Normal => "chore: update runtime dependencies",
+        DependencyKind::Dev => "chore: update dev dependencies",
+        DependencyKind::Build => "chore: update build dependencies",
+        DependencyKind::Workspace => "chore: update workspace dependencies",
+    }
+}
+
+/// Package ID spec of the dependency for `cargo update`, with the newest locked version matching
+/// its requirement, or its current version, as a bare name is rejected when Cargo.lock holds
+/// several versions of the crate
+fn get_package_spec(dependency: &Dependency, locked_packages: &[(String, String)]) -> String {
+    let requirement = VersionReq::parse(&dependency.requirement).ok();
+    let version = locked_packages
+        .iter()
+        .filter(|(name, _)| *name == dependency.name)
+        .filter_map(|(_, version)| Version::parse(version).ok())
+        .filter(|version| requirement.as_ref().is_some_and(|r| r.matches(version)))
+        .max()
+        .map_or_else(|| dependency.current_version.clone(), |v| v.to_string());
+
+    format!("{}@{version}", dependency.name)
+}
+
+/// Version requirement of the dependency in the section of its kind of the Cargo.toml file
+fn get_written_version<'a>(
+    cargo_toml: &'a DocumentMut,
+    dependency: &Dependency,
+) -> Option<&'a str> {
+    let section = match dependency.kind {
+        DependencyKind::Dev => cargo_toml.get("dev-dependencies"),
+        DependencyKind::Build => cargo_toml.get("build-dependencies"),
+        DependencyKind::Workspace => cargo_toml.get("workspace")?.get("dependencies"),
+        DependencyKind::Normal => cargo_toml.get("dependencies"),
+    }?;
+
+    let item = section.get(&dependency.name)?;
+    item.as_str().or_else(|| item.get("version")?.as_str())
+}
+
+/// Writes every file to a temporary file next to it before renaming them over the originals, so
+/// that a failure to write any of them leaves all the original files untouched. A failure to
+/// rename one of them leaves the files renamed before it updated, which the error lists, and the
+/// temporary files are removed either way
+fn write_files_atomically(files: &[(PathBuf, String)]) -> std::io::Result<()> {
+    let temporary_path = |path: &Path| path.with_extension("toml.interactive-update.tmp");
+
+    for (i, (path, content)) in files.iter().enumerate() {
+        if let Err(e) = std::fs::write(temporary_path(path), content) {
+            for (path, _) in files.iter().take(i + 1) {
+                let _ = std::fs::remove_file(temporary_path(path));
+            }
+            return Err(e);
+        }
+    }
+
+    for (i, (path, _)) in files.iter().enumerate() {
+        if let Err(e) = std::fs::rename(temporary_path(path), path) {
+            for (path, _) in &files[i..] {
+                let _ = std::fs::remove_file(temporary_path(path));
+            }
+
+            let updated = files[..i]
+                .iter()
+                .map(|(path, _)| path.display().to_string())
+                .collect::<Vec<_>>();
+            return Err(std::io::Error::new(
+                e.kind(),
+                format!(
+                    "unable to replace {} with its update: {e}, {}",
+                    path.display(),
+                    if updated.is_empty() {
+                        "no file was updated".to_string()
+                    } else {
+                        format!("only {} were updated", updated.join(", "))
+                    }
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Default)]
 pub struct Dependencies {
     pub dependencies: Vec<Dependency>,
     pub cargo_toml_files: HashMap<String, DocumentMut>,
 }
 
+/// Replaces the version value in place, keeping its surrounding whitespace and comments
+fn set_version(item: &mut Item, version: String) {
+    match item.as_value_mut() {
+        Some(current) => {
+            let decor = current.decor().clone();
+            *current = Value::from(version);
+            *current.decor_mut() = decor;
+        }
+        None => *item = value(version),
+    }
+}
+
 impl Dependencies {
     pub fn new(
         dependencies: Vec<Dependency>,
@@ -77,33 +502,321 @@ impl Dependencies {
         self.dependencies.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.dependencies.is_empty()
+    }
+
     pub fn iter(&self) -> std::slice::Iter<'_, Dependency> {
         self.dependencies.iter()
     }
 
-    pub fn apply_versions(&mut self, args: Args) -> Result<(), Box<dyn std::error::Error>> {
-        println!("\n\n");
+    pub fn sort_by_order(&mut self, order: SortOrder) {
+        self.dependencies.sort_by(|a, b| order.compare(a, b));
+    }
+
+    /// Writes the updated versions as asked by the options, returning what was done for the caller
+    /// to report it, without printing anything
+    pub fn apply_versions(&mut self, options: &ApplyOptions) -> Result<ApplyReport, Error> {
+        if self.is_empty() {
+            return Ok(ApplyReport::default());
+        }
+
+        let manifest_dir = &options.manifest_dir;
+
+        if let Some(output_patch) = &options.output_patch {
+            return self.write_patch(output_patch, options);
+        }
+
+        if let Some(output_dir) = &options.output_dir {
+            return self.write_output_dir(output_dir, options);
+        }
+
+        if options.lock_only {
+            return self.update_lock_file(manifest_dir);
+        }
+
+        let mut report = ApplyReport::default();
+
+        if let Some(branch) = &options.branch {
+            if crate::git::create_branch(manifest_dir, branch)? {
+                report.branch = Some(branch.clone());
+            }
+        }
+
+        if options.split_by_kind {
+            for kind in DependencyKind::write_order() {
+                if self.iter().any(|d| d.kind == kind) {
+                    self.write_kinds(&[kind], options, &mut report)?;
+                }
+            }
+        } else {
+            self.write_kinds(&DependencyKind::write_order(), options, &mut report)?;
+        }
+
+        if options.check {
+            let mut command = std::process::Command::new("cargo");
+            command.arg("check");
+            command
+                .arg("--manifest-path")
+                .arg(manifest_dir.join("Cargo.toml"));
+            if options.frozen {
+                command.arg("--frozen");
+            } else if options.locked {
+                command.arg("--locked");
+            }
+            report.check = Some(command.output().map_err(Error::Check)?);
+        }
+
+        Ok(report)
+    }
+
+    /// Writes the updated versions of the given kinds of dependencies to the Cargo.toml files
+    /// declaring them, then commits them if requested, adding them to the report
+    fn write_kinds(
+        &mut self,
+        kinds: &[DependencyKind],
+        options: &ApplyOptions,
+        report: &mut ApplyReport,
+    ) -> Result<(), Error> {
+        let manifest_dir = &options.manifest_dir;
+
+        for kind in kinds {
+            self.apply_versions_by_kind(*kind, options.pin);
+        }
+
+        let workspace_paths = self
+            .iter()
+            .filter(|d| kinds.contains(&d.kind))
+            .map(|d| d.workspace_path.as_deref().unwrap_or("."))
+            .collect::<HashSet<_>>();
+
+        let mut workspace_paths = workspace_paths.into_iter().collect::<Vec<_>>();
+        workspace_paths.sort_by_key(|workspace_path| (*workspace_path != ".", *workspace_path));
+
+        // The root, holding the `[workspace.dependencies]`, is written first
+        let files = workspace_paths
+            .into_iter()
+            .map(|workspace_path| {
+                (
+                    manifest_dir.join(workspace_path).join("Cargo.toml"),
+                    self.cargo_toml_files[workspace_path].to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        write_files_atomically(&files)?;
+
+        self.verify_written_versions(manifest_dir, options.pin, kinds)?;
+
+        report.updated.extend(
+            self.iter()
+                .filter(|d| kinds.contains(&d.kind))
+                .map(|d| d.applied_update(manifest_dir, d.version_requirement(options.pin))),
+        );
+        let paths = files.into_iter().map(|(path, _)| path).collect::<Vec<_>>();
+
+        if options.commit {
+            let message = match kinds {
+                [kind] => get_commit_message(*kind).to_string(),
+                _ => format!("update {} dependencies", self.len()),
+            };
+
+            if crate::git::commit(manifest_dir, &paths, &message)? {
+                report.commits.push(message);
+            }
+        }
+        report.written_files.extend(paths);
+
+        Ok(())
+    }
+
+    /// Writes the changes to the Cargo.toml files as a unified diff to the given file, with paths
+    /// relative to the manifest directory, leaving the Cargo.toml files untouched
+    fn write_patch(
+        &mut self,
+        output_patch: &Path,
+        options: &ApplyOptions,
+    ) -> Result<ApplyReport, Error> {
+        for kind in DependencyKind::write_order() {
+            self.apply_versions_by_kind(kind, options.pin);
+        }
+
+        let workspace_paths = self
+            .iter()
+            .map(|d| d.workspace_path.as_deref().unwrap_or("."))
+            .collect::<HashSet<_>>();
+
+        let mut files = self
+            .cargo_toml_files
+            .iter()
+            .filter(|(workspace_path, _)| workspace_paths.contains(workspace_path.as_str()))
+            .collect::<Vec<_>>();
+        files.sort_by_key(|(workspace_path, _)| workspace_path.as_str());
+
+        let mut patch = String::new();
+        for (workspace_path, cargo_toml) in files {
+            let path = options.manifest_dir.join(workspace_path).join("Cargo.toml");
+            let before = std::fs::read_to_string(&path)?;
+            let relative_path = if workspace_path == "." {
+                "Cargo.toml".to_string()
+            } else {
+                format!("{workspace_path}/Cargo.toml")
+            };
+
+            patch.push_str(&crate::diff::unified_diff(
+                &relative_path,
+                &before,
+                &cargo_toml.to_string(),
+            ));
+        }
+
+        std::fs::write(output_patch, patch)?;
+
+        Ok(ApplyReport {
+            written_files: vec![output_patch.to_path_buf()],
+            updated: self.applied_updates(&options.manifest_dir, options.pin),
+            ..Default::default()
+        })
+    }
+
+    /// Updates Cargo.lock to the latest versions already allowed by the requirements, with
+    /// `cargo update --precise`, reporting the other ones as needing a new requirement. Every
+    /// update is attempted, the ones that failed being reported at the end
+    fn update_lock_file(&self, manifest_dir: &Path) -> Result<ApplyReport, Error> {
+        let (compatible, incompatible): (Vec<_>, Vec<_>) =
+            self.iter().partition(|d| d.is_compatible_update());
+        let locked_packages = crate::cargo::get_locked_packages(manifest_dir);
 
-        if self.dependencies.is_empty() {
-            println!("No dependencies have been updated.");
-            return Ok(());
+        // The same crate can be declared by several members, with a single entry in Cargo.lock
+        let mut updated = vec![];
+        let mut failed = vec![];
+        for dependency in &compatible {
+            let update = (&dependency.name, &dependency.latest_version);
+            if updated.contains(&update) || failed.contains(&update) {
+                continue;
+            }
+
+            let output = std::process::Command::new("cargo")
+                .arg("update")
+                .arg("--manifest-path")
+                .arg(manifest_dir.join("Cargo.toml"))
+                .args(["--package", &get_package_spec(dependency, &locked_packages)])
+                .args(["--precise", &dependency.latest_version])
+                .output()
+                .map_err(|e| Error::Lock(format!("unable to run `cargo update`: {e}")))?;
+            if output.status.success() {
+                updated.push(update);
+            } else {
+                failed.push(update);
+            }
         }
 
-        for kind in DependencyKind::ordered() {
-            self.apply_versions_by_kind(kind, args.pin);
+        if !failed.is_empty() {
+            return Err(Error::Lock(format!(
+                "failed to update {} with `cargo update`",
+                failed
+                    .iter()
+                    .map(|(name, version)| format!("{name} to {version}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
         }
 
-        for (workspace_path, cargo_toml) in self.cargo_toml_files.iter() {
-            std::fs::write(
-                format!("{}/Cargo.toml", workspace_path),
-                cargo_toml.to_string(),
-            )?;
-            println!("Dependencies have been updated in Cargo.toml.");
+        let applied_update = |dependency: &Dependency| {
+            dependency.applied_update(manifest_dir, dependency.latest_version.clone())
+        };
+        Ok(ApplyReport {
+            written_files: vec![manifest_dir.join("Cargo.lock")],
+            updated: compatible
+                .into_iter()
+                .filter(|d| updated.contains(&(&d.name, &d.latest_version)))
+                .map(applied_update)
+                .collect(),
+            skipped: incompatible.into_iter().map(applied_update).collect(),
+            ..Default::default()
+        })
+    }
+
+    /// Writes the updated Cargo.toml files of the package and all its workspace members under the
+    /// given directory, at the same paths relative to it as to the manifest directory, leaving the
+    /// original files untouched
+    fn write_output_dir(
+        &mut self,
+        output_dir: &Path,
+        options: &ApplyOptions,
+    ) -> Result<ApplyReport, Error> {
+        for kind in DependencyKind::write_order() {
+            self.apply_versions_by_kind(kind, options.pin);
+        }
+
+        let mut files = self
+            .cargo_toml_files
+            .iter()
+            .map(|(workspace_path, cargo_toml)| {
+                (
+                    output_dir.join(workspace_path).join("Cargo.toml"),
+                    cargo_toml.to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+
+        for (path, content) in &files {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(path, content)?;
         }
 
-        if !args.no_check {
-            println!("\nExecuting {}...", "cargo check".bold());
-            std::process::Command::new("cargo").arg("check").status()?;
+        Ok(ApplyReport {
+            written_files: files.into_iter().map(|(path, _)| path).collect(),
+            updated: self.applied_updates(&options.manifest_dir, options.pin),
+            ..Default::default()
+        })
+    }
+
+    /// Updates of all the dependencies, as written to the Cargo.toml files
+    fn applied_updates(&self, manifest_dir: &Path, pin: bool) -> Vec<AppliedUpdate> {
+        self.iter()
+            .map(|d| d.applied_update(manifest_dir, d.version_requirement(pin)))
+            .collect()
+    }
+
+    /// Reads the written Cargo.toml files back, making sure each dependency of the given kinds now
+    /// requires its new version
+    fn verify_written_versions(
+        &self,
+        manifest_dir: &Path,
+        pin: bool,
+        kinds: &[DependencyKind],
+    ) -> Result<(), Error> {
+        let mut cargo_toml_files = HashMap::new();
+
+        for dependency in self.iter().filter(|d| kinds.contains(&d.kind)) {
+            let path = manifest_dir
+                .join(dependency.workspace_path.as_deref().unwrap_or("."))
+                .join("Cargo.toml");
+
+            if !cargo_toml_files.contains_key(&path) {
+                let cargo_toml = std::fs::read_to_string(&path)
+                    .map_err(|e| {
+                        Error::Manifest(format!("Unable to read {}: {e}", path.display()))
+                    })?
+                    .parse::<DocumentMut>()
+                    .map_err(|e| {
+                        Error::Manifest(format!("Unable to parse {}: {e}", path.display()))
+                    })?;
+                cargo_toml_files.insert(path.clone(), cargo_toml);
+            }
+
+            let version = dependency.version_requirement(pin);
+            if get_written_version(&cargo_toml_files[&path], dependency) != Some(version.as_str()) {
+                return Err(Error::NotUpdated(format!(
+                    "{} was not updated to {version} in {}",
+                    dependency.name,
+                    path.display()
+                )));
+            }
         }
 
         Ok(())
@@ -121,11 +834,7 @@ impl Dependencies {
                 )
                 .unwrap();
 
-            let version = if pin {
-                value(format!("={}", dependency.latest_version))
-            } else {
-                value(&dependency.latest_version)
-            };
+            let version = dependency.version_requirement(pin);
 
             let section = match kind {
                 DependencyKind::Dev => cargo_toml.get_mut("dev-dependencies"),
@@ -135,14 +844,80 @@ impl Dependencies {
             }
             .unwrap();
 
-            if matches!(section[&dependency.name], Item::Value(Value::String(_))) {
-                section[&dependency.name] = version
+            // Inherited from `[workspace.dependencies]`, where its version is written instead
+            if section[&dependency.name]
+                .get("workspace")
+                .and_then(Item::as_bool)
+                .unwrap_or(false)
+            {
+                continue;
+            }
+
+            let item = if matches!(section[&dependency.name], Item::Value(Value::String(_))) {
+                &mut section[&dependency.name]
             } else {
-                section[&dependency.name]["version"] = version
+                &mut section[&dependency.name]["version"]
+            };
+
+            set_version(item, version);
+        }
+    }
+
+    pub fn with_advisories_only(self) -> Self {
+        let selected = self.iter().map(|d| !d.advisories.is_empty()).collect();
+        self.filter_selected_dependencies(selected)
+    }
+
+    /// Targets the nearest version that wasn't yanked, instead of the latest one, for the
+    /// dependencies whose current version was yanked, to leave them with the smallest update
+    pub fn target_nearest_non_yanked_versions(&mut self) {
+        for dependency in self
+            .dependencies
+            .iter_mut()
+            .filter(|d| d.current_version_yanked)
+        {
+            let target = dependency.nearest_non_yanked_version().to_string();
+            if target != dependency.latest_version {
+                dependency.retarget(target);
             }
         }
     }
 
+    /// Falls back to the newest version released since the current one that is not ignored when
+    /// the latest one is, dropping the dependencies left without any
+    pub fn skip_ignored_versions(&mut self, ignored: &[IgnoredVersion]) {
+        if ignored.is_empty() {
+            return;
+        }
+
+        let is_ignored =
+            |name: &str, version: &str| ignored.iter().any(|i| i.matches(name, version));
+        self.dependencies.retain_mut(|dependency| {
+            if !is_ignored(&dependency.name, &dependency.latest_version) {
+                return true;
+            }
+
+            let target = dependency
+                .intermediate_versions
+                .iter()
+                .rev()
+                .find(|version| !is_ignored(&dependency.name, version))
+                .cloned();
+            match target {
+                Some(target) => {
+                    dependency.retarget(target);
+                    true
+                }
+                None => false,
+            }
+        });
+    }
+
+    pub fn without_major_updates(self) -> Self {
+        let selected = self.iter().map(|d| !d.is_major_update()).collect();
+        self.filter_selected_dependencies(selected)
+    }
+
     pub fn has_workspace_members(&self) -> bool {
         self.dependencies.iter().any(|d| d.workspace_path.is_some())
     }
@@ -181,3 +956,796 @@ impl IntoIterator for Dependencies {
         self.dependencies.into_iter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_major_update() {
+        let dependency = |current_version: &str, latest_version: &str| Dependency {
+            current_version: current_version.to_string(),
+            latest_version: latest_version.to_string(),
+            ..Default::default()
+        };
+
+        assert!(dependency("1.2.3", "2.0.0").is_major_update());
+        assert!(!dependency("1.2.3", "1.3.0").is_major_update());
+        assert!(!dependency("1.2.3", "1.2.4").is_major_update());
+        assert!(dependency("0.1.3", "0.2.0").is_major_update());
+        assert!(dependency("0.1.3", "1.0.0").is_major_update());
+        assert!(!dependency("0.1.3", "0.1.4").is_major_update());
+        assert!(dependency("0.0.1", "0.0.2").is_major_update());
+        assert!(!dependency("1.0", "2.0.0").is_major_update());
+    }
+
+    #[test]
+    fn test_update_type() {
+        let dependency = |current_version: &str, latest_version: &str| Dependency {
+            current_version: current_version.to_string(),
+            latest_version: latest_version.to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            dependency("1.2.3", "2.0.0").update_type(),
+            Some(UpdateType::Major)
+        );
+        assert_eq!(
+            dependency("1.2.3", "1.3.0").update_type(),
+            Some(UpdateType::Minor)
+        );
+        assert_eq!(
+            dependency("1.2.3", "1.2.4").update_type(),
+            Some(UpdateType::Patch)
+        );
+        assert_eq!(
+            dependency("0.1.3", "0.2.0").update_type(),
+            Some(UpdateType::Major)
+        );
+        assert_eq!(
+            dependency("0.1.3", "0.1.4").update_type(),
+            Some(UpdateType::Patch)
+        );
+        assert_eq!(dependency("1.0", "2.0.0").update_type(), None);
+    }
+
+    #[test]
+    fn test_version_requirement() {
+        let mut dependency = Dependency {
+            latest_version: "1.2.3".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(dependency.version_requirement(false), "1.2.3");
+        assert_eq!(dependency.version_requirement(true), "=1.2.3");
+
+        dependency.operator = Some(VersionOperator::Caret);
+        assert_eq!(dependency.version_requirement(true), "^1.2.3");
+
+        dependency.operator = Some(VersionOperator::Tilde);
+        assert_eq!(dependency.version_requirement(false), "~1.2.3");
+
+        dependency.operator = Some(VersionOperator::Bare);
+        assert_eq!(dependency.version_requirement(true), "1.2.3");
+
+        dependency.operator = None;
+        dependency.requirement = "~1.1".to_string();
+        assert_eq!(dependency.version_requirement(false), "~1.2.3");
+        assert_eq!(dependency.version_requirement(true), "=1.2.3");
+    }
+
+    #[test]
+    fn test_sort_by_order() {
+        let dependency = |name: &str, latest_version: &str, date: &str, kind| Dependency {
+            name: name.to_string(),
+            current_version: "1.0.0".to_string(),
+            latest_version: latest_version.to_string(),
+            latest_version_date: Some(date.to_string()),
+            kind,
+            ..Default::default()
+        };
+        let mut dependencies = Dependencies::new(
+            vec![
+                dependency("a", "1.0.1", "2024-03-01", DependencyKind::Normal),
+                dependency("b", "2.0.0", "2024-01-01", DependencyKind::Normal),
+                dependency("c", "1.1.0", "2024-02-01", DependencyKind::Normal),
+                dependency("d", "3.0.0", "2024-04-01", DependencyKind::Dev),
+            ],
+            HashMap::new(),
+        );
+        let names = |dependencies: &Dependencies| {
+            dependencies
+                .iter()
+                .map(|d| d.name.as_str())
+                .collect::<Vec<_>>()
+                .join("")
+        };
+
+        dependencies.sort_by_order(SortOrder::Age);
+        assert_eq!(names(&dependencies), "acbd");
+
+        dependencies.sort_by_order(SortOrder::Delta);
+        assert_eq!(names(&dependencies), "bcad");
+
+        dependencies.sort_by_order(SortOrder::Name);
+        assert_eq!(names(&dependencies), "abcd");
+
+        // Dependencies with security advisories come first whatever the order
+        dependencies.dependencies[2].advisories = vec!["RUSTSEC-2024-0001".to_string()];
+        dependencies.sort_by_order(SortOrder::Age);
+        assert_eq!(names(&dependencies), "cabd");
+
+        dependencies.sort_by_order(SortOrder::Delta);
+        assert_eq!(names(&dependencies), "cbad");
+    }
+
+    #[test]
+    fn test_releases_behind() {
+        let dependency = |current_version: &str, intermediate_versions: &[&str]| Dependency {
+            current_version: current_version.to_string(),
+            latest_version: "1.3.0".to_string(),
+            intermediate_versions: intermediate_versions
+                .iter()
+                .map(|v| v.to_string())
+                .collect(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            dependency("1.0.0", &["1.1.0", "1.2.0"]).releases_behind(),
+            3
+        );
+        assert_eq!(dependency("1.2.0", &[]).releases_behind(), 1);
+        assert_eq!(dependency("1.3.0", &[]).releases_behind(), 0);
+        assert_eq!(dependency("1.0", &[]).releases_behind(), 0);
+    }
+
+    #[test]
+    fn test_target_nearest_non_yanked_versions() {
+        let dependency = |name: &str, requirement: &str, current_version_yanked| Dependency {
+            name: name.to_string(),
+            requirement: requirement.to_string(),
+            current_version: "1.0.0".to_string(),
+            latest_version: "2.0.0".to_string(),
+            latest_version_date: Some("2023-07-02T00:00:00Z".to_string()),
+            intermediate_versions: vec!["1.0.2".to_string(), "1.1.0".to_string()],
+            yanked_versions: vec!["1.0.1".to_string(), "1.2.0".to_string()],
+            current_version_yanked,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            dependency("a", "=1.0.0", true).nearest_non_yanked_version(),
+            "2.0.0"
+        );
+        assert_eq!(
+            dependency("a", "~1.0.0", true).nearest_non_yanked_version(),
+            "1.0.2"
+        );
+        assert_eq!(
+            dependency("a", "^1.0.0", true).nearest_non_yanked_version(),
+            "1.0.2"
+        );
+
+        let mut dependencies = Dependencies::new(
+            vec![
+                dependency("a", "~1.0.0", true),
+                dependency("b", "~1.0.0", false),
+            ],
+            HashMap::new(),
+        );
+        dependencies.target_nearest_non_yanked_versions();
+
+        let yanked = &dependencies.dependencies[0];
+        assert_eq!(yanked.latest_version, "1.0.2");
+        assert_eq!(yanked.latest_version_date, None);
+        assert!(yanked.intermediate_versions.is_empty());
+        assert_eq!(yanked.yanked_versions, ["1.0.1"]);
+        assert_eq!(dependencies.dependencies[1].latest_version, "2.0.0");
+    }
+
+    #[test]
+    fn test_get_package_spec() {
+        let dependency = Dependency {
+            name: "syn".to_string(),
+            requirement: "2.0".to_string(),
+            current_version: "2.0.0".to_string(),
+            latest_version: "2.0.87".to_string(),
+            ..Default::default()
+        };
+        let package = |name: &str, version: &str| (name.to_string(), version.to_string());
+
+        assert_eq!(
+            get_package_spec(
+                &dependency,
+                &[
+                    package("syn", "1.0.109"),
+                    package("syn", "2.0.50"),
+                    package("serde", "1.0.210"),
+                ]
+            ),
+            "syn@2.0.50"
+        );
+        assert_eq!(
+            get_package_spec(&dependency, &[package("syn", "1.0.109")]),
+            "syn@2.0.0"
+        );
+    }
+
+    #[test]
+    fn test_is_compatible_update() {
+        let dependency = |requirement: &str, latest_version: &str| Dependency {
+            requirement: requirement.to_string(),
+            latest_version: latest_version.to_string(),
+            ..Default::default()
+        };
+
+        assert!(dependency("1.0", "1.5.2").is_compatible_update());
+        assert!(dependency("~1.2.0", "1.2.9").is_compatible_update());
+        assert!(!dependency("~1.2.0", "1.3.0").is_compatible_update());
+        assert!(!dependency("=1.0.100", "1.0.210").is_compatible_update());
+        assert!(!dependency("0.7", "0.8.0").is_compatible_update());
+    }
+
+    #[test]
+    fn test_parse_ignored_version() {
+        assert_eq!(
+            "serde@1.0.211".parse(),
+            Ok(IgnoredVersion {
+                name: "serde".to_string(),
+                version: Version::new(1, 0, 211),
+                up_to: false,
+            })
+        );
+        assert_eq!(
+            "toml@<=0.8.0".parse(),
+            Ok(IgnoredVersion {
+                name: "toml".to_string(),
+                version: Version::new(0, 8, 0),
+                up_to: true,
+            })
+        );
+        assert!("serde".parse::<IgnoredVersion>().is_err());
+        assert!("serde@1.0".parse::<IgnoredVersion>().is_err());
+    }
+
+    #[test]
+    fn test_skip_ignored_versions() {
+        let dependency = |name: &str| Dependency {
+            name: name.to_string(),
+            current_version: "1.0.0".to_string(),
+            latest_version: "2.0.0".to_string(),
+            latest_version_date: Some("2023-07-02T00:00:00Z".to_string()),
+            intermediate_versions: vec!["1.0.2".to_string(), "1.1.0".to_string()],
+            ..Default::default()
+        };
+
+        let mut dependencies = Dependencies::new(
+            vec![dependency("a"), dependency("b"), dependency("c")],
+            HashMap::new(),
+        );
+        dependencies.skip_ignored_versions(&[
+            "a@2.0.0".parse().unwrap(),
+            "b@<=2.0.0".parse().unwrap(),
+            "c@1.1.0".parse().unwrap(),
+        ]);
+
+        assert_eq!(dependencies.len(), 2);
+        let a = &dependencies.dependencies[0];
+        assert_eq!(a.latest_version, "1.1.0");
+        assert_eq!(a.latest_version_date, None);
+        assert_eq!(a.intermediate_versions, ["1.0.2"]);
+        assert_eq!(dependencies.dependencies[1].name, "c");
+        assert_eq!(dependencies.dependencies[1].latest_version, "2.0.0");
+    }
+
+    #[test]
+    fn test_is_deprecated() {
+        let dependency = |description: Option<&str>| Dependency {
+            description: description.map(str::to_string),
+            ..Default::default()
+        };
+
+        assert!(dependency(Some("DEPRECATED: use other-crate instead")).is_deprecated());
+        assert!(dependency(Some("This crate is no longer maintained")).is_deprecated());
+        assert!(dependency(Some("Unmaintained, see foo")).is_deprecated());
+        assert!(!dependency(Some("A serialization framework")).is_deprecated());
+        assert!(!dependency(None).is_deprecated());
+    }
+
+    #[test]
+    fn test_requirement_operator() {
+        let dependency = |requirement: &str| Dependency {
+            requirement: requirement.to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            dependency("1.2").requirement_operator(),
+            VersionOperator::Bare
+        );
+        assert_eq!(
+            dependency("^1.2").requirement_operator(),
+            VersionOperator::Caret
+        );
+        assert_eq!(
+            dependency("~1.2").requirement_operator(),
+            VersionOperator::Tilde
+        );
+        assert_eq!(
+            dependency("= 1.2.3").requirement_operator(),
+            VersionOperator::Exact
+        );
+    }
+
+    #[test]
+    fn test_version_operator_cycle() {
+        let mut operator = None;
+        let mut operators = vec![];
+        for _ in 0..5 {
+            operator = VersionOperator::cycle(operator);
+            operators.push(operator);
+        }
+        assert_eq!(
+            operators,
+            vec![
+                Some(VersionOperator::Caret),
+                Some(VersionOperator::Tilde),
+                Some(VersionOperator::Exact),
+                Some(VersionOperator::Bare),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_write_files_atomically() {
+        let dir = std::env::temp_dir().join(format!("write-atomically-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Cargo.toml");
+        std::fs::write(&path, "before").unwrap();
+
+        let missing_dir_path = dir.join("missing").join("Cargo.toml");
+        let result = write_files_atomically(&[
+            (path.clone(), "after".to_string()),
+            (missing_dir_path, "after".to_string()),
+        ]);
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "before");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        write_files_atomically(&[(path.clone(), "after".to_string())]).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "after");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_files_atomically_failed_rename() {
+        let dir = std::env::temp_dir().join(format!("failed-rename-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("member")).unwrap();
+        let path = dir.join("Cargo.toml");
+        std::fs::write(&path, "before").unwrap();
+        // A file can't be renamed over a directory that isn't empty
+        let directory_path = dir.join("member").join("Cargo.toml");
+        std::fs::create_dir_all(directory_path.join("nested")).unwrap();
+
+        let error = write_files_atomically(&[
+            (path.clone(), "after".to_string()),
+            (directory_path.clone(), "after".to_string()),
+        ])
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains(&format!("only {} were updated", path.display())));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "after");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+        assert_eq!(std::fs::read_dir(dir.join("member")).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_patch() {
+        let dir = std::env::temp_dir().join(format!("write-patch-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("member")).unwrap();
+        let root_cargo_toml = "[workspace]\nmembers = [\"member\"]\n";
+        let member_cargo_toml = "[dependencies]\nserde = \"1.0.100\"\n";
+        std::fs::write(dir.join("Cargo.toml"), root_cargo_toml).unwrap();
+        std::fs::write(dir.join("member").join("Cargo.toml"), member_cargo_toml).unwrap();
+
+        let mut dependencies = Dependencies::new(
+            vec![Dependency {
+                name: "serde".to_string(),
+                requirement: "1.0.100".to_string(),
+                latest_version: "1.0.210".to_string(),
+                workspace_path: Some("member".to_string()),
+                ..Default::default()
+            }],
+            HashMap::from([
+                (".".to_string(), root_cargo_toml.parse().unwrap()),
+                ("member".to_string(), member_cargo_toml.parse().unwrap()),
+            ]),
+        );
+        let output_patch = dir.join("updates.patch");
+        let report = dependencies
+            .write_patch(
+                &output_patch,
+                &ApplyOptions {
+                    manifest_dir: dir.clone(),
+                    output_patch: Some(output_patch.clone()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(report.written_files, [output_patch.clone()]);
+        assert_eq!(report.updated.len(), 1);
+        assert_eq!(
+            report.updated[0].manifest_path,
+            dir.join("member").join("Cargo.toml")
+        );
+
+        assert_eq!(
+            std::fs::read_to_string(&output_patch).unwrap(),
+            concat!(
+                "diff --git a/member/Cargo.toml b/member/Cargo.toml\n",
+                "--- a/member/Cargo.toml\n",
+                "+++ b/member/Cargo.toml\n",
+                "@@ -1,2 +1,2 @@\n",
+                " [dependencies]\n",
+                "-serde = \"1.0.100\"\n",
+                "+serde = \"1.0.210\"\n",
+            )
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("member").join("Cargo.toml")).unwrap(),
+            member_cargo_toml
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_apply_versions_inherited_from_workspace() {
+        let root_cargo_toml = "[workspace]\nmembers = [\"member\"]\n\n[workspace.dependencies]\nserde = \"1.0.100\"\n";
+        let member_cargo_toml =
+            "[dependencies]\nserde = { workspace = true, features = [\"derive\"] }\n";
+
+        let mut dependencies = Dependencies::new(
+            vec![
+                Dependency {
+                    name: "serde".to_string(),
+                    requirement: "1.0.100".to_string(),
+                    latest_version: "1.0.210".to_string(),
+                    kind: DependencyKind::Normal,
+                    workspace_path: Some("member".to_string()),
+                    ..Default::default()
+                },
+                Dependency {
+                    name: "serde".to_string(),
+                    requirement: "1.0.100".to_string(),
+                    latest_version: "1.0.210".to_string(),
+                    kind: DependencyKind::Workspace,
+                    ..Default::default()
+                },
+            ],
+            HashMap::from([
+                (".".to_string(), root_cargo_toml.parse().unwrap()),
+                ("member".to_string(), member_cargo_toml.parse().unwrap()),
+            ]),
+        );
+        for kind in DependencyKind::write_order() {
+            dependencies.apply_versions_by_kind(kind, false);
+        }
+
+        assert_eq!(
+            dependencies.cargo_toml_files["."].to_string(),
+            root_cargo_toml.replace("1.0.100", "1.0.210")
+        );
+        assert_eq!(
+            dependencies.cargo_toml_files["member"].to_string(),
+            member_cargo_toml
+        );
+    }
+
+    #[test]
+    fn test_apply_versions_same_dependency_in_several_sections() {
+        let cargo_toml = "[dependencies]\nserde = \"1.0.100\"\n\n[dev-dependencies]\nserde = { version = \"1.0.50\", features = [\"derive\"] }\n";
+        let dependency = |requirement: &str, kind| Dependency {
+            name: "serde".to_string(),
+            requirement: requirement.to_string(),
+            latest_version: "1.0.210".to_string(),
+            kind,
+            ..Default::default()
+        };
+
+        let mut dependencies = Dependencies::new(
+            vec![
+                dependency("1.0.100", DependencyKind::Normal),
+                dependency("1.0.50", DependencyKind::Dev),
+            ],
+            HashMap::from([(".".to_string(), cargo_toml.parse().unwrap())]),
+        );
+        for kind in DependencyKind::write_order() {
+            dependencies.apply_versions_by_kind(kind, false);
+        }
+
+        let cargo_toml = &dependencies.cargo_toml_files["."];
+        assert_eq!(
+            cargo_toml.to_string(),
+            "[dependencies]\nserde = \"1.0.210\"\n\n[dev-dependencies]\nserde = { version = \"1.0.210\", features = [\"derive\"] }\n"
+        );
+        for dependency in dependencies.iter() {
+            assert_eq!(get_written_version(cargo_toml, dependency), Some("1.0.210"));
+        }
+    }
+
+    #[test]
+    fn test_write_output_dir() {
+        let dir = std::env::temp_dir().join(format!("write-output-dir-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("member")).unwrap();
+        let root_cargo_toml = "[workspace]\nmembers = [\"member\"]\n";
+        let member_cargo_toml = "[dependencies]\nserde = \"1.0.100\"\n";
+        std::fs::write(dir.join("Cargo.toml"), root_cargo_toml).unwrap();
+        std::fs::write(dir.join("member").join("Cargo.toml"), member_cargo_toml).unwrap();
+
+        let mut dependencies = Dependencies::new(
+            vec![Dependency {
+                name: "serde".to_string(),
+                requirement: "1.0.100".to_string(),
+                latest_version: "1.0.210".to_string(),
+                workspace_path: Some("member".to_string()),
+                ..Default::default()
+            }],
+            HashMap::from([
+                (".".to_string(), root_cargo_toml.parse().unwrap()),
+                ("member".to_string(), member_cargo_toml.parse().unwrap()),
+            ]),
+        );
+        let output_dir = dir.join("preview");
+        let report = dependencies
+            .write_output_dir(
+                &output_dir,
+                &ApplyOptions {
+                    manifest_dir: dir.clone(),
+                    output_dir: Some(output_dir.clone()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            report.written_files,
+            [
+                output_dir.join("Cargo.toml"),
+                output_dir.join("member").join("Cargo.toml")
+            ]
+        );
+
+        assert_eq!(
+            std::fs::read_to_string(output_dir.join("Cargo.toml")).unwrap(),
+            root_cargo_toml
+        );
+        assert_eq!(
+            std::fs::read_to_string(output_dir.join("member").join("Cargo.toml")).unwrap(),
+            "[dependencies]\nserde = \"1.0.210\"\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("member").join("Cargo.toml")).unwrap(),
+            member_cargo_toml
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_verify_written_versions() {
+        let dir = std::env::temp_dir().join(format!("verify-written-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("member")).unwrap();
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            r#"[dependencies]
+foo = "2.0.0"
+
+[workspace.dependencies]
+bar = { version = "=0.3.1" }
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("member").join("Cargo.toml"),
+            r#"[dev-dependencies]
+baz = "1"
+"#,
+        )
+        .unwrap();
+
+        let dependency =
+            |name: &str, kind, latest_version: &str, workspace_path: Option<&str>| Dependency {
+                name: name.to_string(),
+                requirement: "1".to_string(),
+                latest_version: latest_version.to_string(),
+                kind,
+                workspace_path: workspace_path.map(str::to_string),
+                ..Default::default()
+            };
+        let dependencies = Dependencies::new(
+            vec![
+                dependency("foo", DependencyKind::Normal, "2.0.0", None),
+                dependency("bar", DependencyKind::Workspace, "0.3.1", None),
+                dependency("baz", DependencyKind::Dev, "1.2.0", Some("member")),
+            ],
+            HashMap::new(),
+        );
+
+        assert!(dependencies
+            .verify_written_versions(&dir, false, &[DependencyKind::Normal])
+            .is_ok());
+        assert!(dependencies
+            .verify_written_versions(&dir, true, &[DependencyKind::Workspace])
+            .is_ok());
+        assert!(dependencies
+            .verify_written_versions(&dir, false, &[DependencyKind::Workspace])
+            .is_err());
+        assert_eq!(
+            dependencies
+                .verify_written_versions(&dir, false, &[DependencyKind::Dev])
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "baz was not updated to 1.2.0 in {}",
+                dir.join("member").join("Cargo.toml").display()
+            )
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_ordering_with_advisories() {
+        let dependency = |name: &str, kind, advisories: &[&str]| Dependency {
+            name: name.to_string(),
+            kind,
+            advisories: advisories.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        };
+
+        let mut dependencies = vec![
+            dependency("a", DependencyKind::Normal, &[]),
+            dependency("b", DependencyKind::Dev, &["RUSTSEC-2023-0001"]),
+            dependency("c", DependencyKind::Normal, &["RUSTSEC-2023-0002"]),
+            dependency("d", DependencyKind::Normal, &[]),
+        ];
+        dependencies.sort();
+
+        assert_eq!(
+            dependencies
+                .iter()
+                .map(|d| d.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["c", "a", "d", "b"]
+        );
+    }
+
+    #[test]
+    fn test_apply_versions_by_kind_preserves_other_keys() {
+        let cargo_toml = r#"[dependencies]
+foo = { version = "1", default-features = false, features = ["x"] } # keep foo
+bar = "0.1" # keep bar
+
+[dependencies.baz]
+version = "0.2" # keep baz
+default-features = false
+features = ["y", "z"]
+"#;
+
+        let dependency = |name: &str, requirement: &str, latest_version: &str| Dependency {
+            name: name.to_string(),
+            requirement: requirement.to_string(),
+            latest_version: latest_version.to_string(),
+            ..Default::default()
+        };
+
+        let mut dependencies = Dependencies::new(
+            vec![
+                dependency("foo", "1", "2.0.0"),
+                dependency("bar", "0.1", "0.2.0"),
+                dependency("baz", "0.2", "0.3.1"),
+            ],
+            HashMap::from([(".".to_string(), cargo_toml.parse().unwrap())]),
+        );
+        dependencies.apply_versions_by_kind(DependencyKind::Normal, false);
+
+        assert_eq!(
+            dependencies.cargo_toml_files["."].to_string(),
+            r#"[dependencies]
+foo = { version = "2.0.0", default-features = false, features = ["x"] } # keep foo
+bar = "0.2.0" # keep bar
+
+[dependencies.baz]
+version = "0.3.1" # keep baz
+default-features = false
+features = ["y", "z"]
+"#
+        );
+    }
+
+    #[test]
+    fn test_apply_versions_by_kind_virtual_workspace() {
+        let cargo_toml = r#"[workspace]
+members = ["member"]
+
+[workspace.dependencies]
+serde = { version = "1.0.100", features = ["derive"] }
+tokio = "1.30"
+
+[workspace.dependencies.toml]
+version = "0.7"
+"#;
+
+        let dependency = |name: &str, requirement: &str, latest_version: &str| Dependency {
+            name: name.to_string(),
+            requirement: requirement.to_string(),
+            latest_version: latest_version.to_string(),
+            kind: DependencyKind::Workspace,
+            package_name: Some(String::new()),
+            ..Default::default()
+        };
+
+        let mut dependencies = Dependencies::new(
+            vec![
+                dependency("serde", "1.0.100", "1.0.210"),
+                dependency("tokio", "1.30", "1.40.0"),
+                dependency("toml", "0.7", "0.8.19"),
+            ],
+            HashMap::from([
+                (".".to_string(), cargo_toml.parse().unwrap()),
+                (
+                    "member".to_string(),
+                    "[dependencies]\nserde = { workspace = true }\n"
+                        .parse()
+                        .unwrap(),
+                ),
+            ]),
+        );
+        dependencies.apply_versions_by_kind(DependencyKind::Workspace, false);
+
+        assert_eq!(
+            dependencies.cargo_toml_files["."].to_string(),
+            r#"[workspace]
+members = ["member"]
+
+[workspace.dependencies]
+serde = { version = "1.0.210", features = ["derive"] }
+tokio = "1.40.0"
+
+[workspace.dependencies.toml]
+version = "0.8.19"
+"#
+        );
+        assert_eq!(
+            dependencies.cargo_toml_files["member"].to_string(),
+            "[dependencies]\nserde = { workspace = true }\n"
+        );
+        for dependency in dependencies.iter() {
+            assert_eq!(
+                get_written_version(&dependencies.cargo_toml_files["."], dependency),
+                Some(dependency.latest_version.as_str())
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_commit_message() {
+        assert_eq!(
+            get_commit_message(DependencyKind::Normal),
+            "chore: update runtime dependencies"
+        );
+        assert_eq!(
+            get_commit_message(DependencyKind::Dev),
+            "chore: update dev dependencies"
+        );
+    }
+}
@@ -0,0 +1,192 @@
+/// Lines of context around each change, as with `diff -u` and `git diff`
+const CONTEXT: usize = 3;
+
+enum Op<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+impl Op<'_> {
+    fn in_before(&self) -> bool {
+        !matches!(self, Op::Insert(_))
+    }
+
+    fn in_after(&self) -> bool {
+        !matches!(self, Op::Delete(_))
+    }
+}
+
+/// Unified diff between two versions of the file at the given path, relative to the root of the
+/// patch, in the format of `git diff` so that it can be applied with `git apply`. Empty when the
+/// contents are the same
+pub fn unified_diff(path: &str, before: &str, after: &str) -> String {
+    let before = before.split_inclusive('\n').collect::<Vec<_>>();
+    let after = after.split_inclusive('\n').collect::<Vec<_>>();
+    let ops = diff_lines(&before, &after);
+
+    let changes = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(_)))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // Changes separated by less than twice the context share the same hunk
+    let mut hunks = vec![(changes[0], changes[0])];
+    for &change in &changes[1..] {
+        let last = hunks.last_mut().unwrap();
+        if change - last.1 - 1 <= 2 * CONTEXT {
+            last.1 = change;
+        } else {
+            hunks.push((change, change));
+        }
+    }
+
+    let mut patch = format!("diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n");
+
+    for (first, last) in hunks {
+        let start = first.saturating_sub(CONTEXT);
+        let end = (last + CONTEXT + 1).min(ops.len());
+        let before_start = ops[..start].iter().filter(|op| op.in_before()).count();
+        let after_start = ops[..start].iter().filter(|op| op.in_after()).count();
+        let before_len = ops[start..end].iter().filter(|op| op.in_before()).count();
+        let after_len = ops[start..end].iter().filter(|op| op.in_after()).count();
+
+        patch.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            format_range(before_start, before_len),
+            format_range(after_start, after_len)
+        ));
+
+        for op in &ops[start..end] {
+            let (prefix, line) = match op {
+                Op::Equal(line) => (' ', line),
+                Op::Delete(line) => ('-', line),
+                Op::Insert(line) => ('+', line),
+            };
+            patch.push(prefix);
+            patch.push_str(line);
+            if !line.ends_with('\n') {
+                patch.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    patch
+}
+
+/// Start line and number of lines of a hunk, the start being the line before it when it is empty
+fn format_range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{len}", start + 1),
+    }
+}
+
+/// Shortest edit from the lines before to the lines after, through their longest common
+/// subsequence, deleted lines coming before the inserted ones
+fn diff_lines<'a>(before: &[&'a str], after: &[&'a str]) -> Vec<Op<'a>> {
+    let (n, m) = (before.len(), after.len());
+
+    // Length of the longest common subsequence of `before[i..]` and `after[j..]`
+    let mut lcs = vec![vec![0; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if before[i] == after[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut ops = Vec::with_capacity(n.max(m));
+    while i < n || j < m {
+        if i < n && j < m && before[i] == after[j] {
+            ops.push(Op::Equal(before[i]));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(Op::Delete(before[i]));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(after[j]));
+            j += 1;
+        }
+    }
+
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let before = "[package]\nname = \"foo\"\n\n[dependencies]\nserde = \"1.0.100\"\ntoml = \"0.7\"\n\n[dev-dependencies]\na = \"1\"\nb = \"1\"\nc = \"1\"\nd = \"1\"\ne = \"1\"\nf = \"1\"\ng = \"1\"\n";
+        let after = before
+            .replace("serde = \"1.0.100\"", "serde = \"1.0.210\"")
+            .replace("g = \"1\"", "g = \"2\"");
+
+        assert_eq!(
+            unified_diff("member/Cargo.toml", before, &after),
+            concat!(
+                "diff --git a/member/Cargo.toml b/member/Cargo.toml\n",
+                "--- a/member/Cargo.toml\n",
+                "+++ b/member/Cargo.toml\n",
+                "@@ -2,7 +2,7 @@\n",
+                " name = \"foo\"\n",
+                " \n",
+                " [dependencies]\n",
+                "-serde = \"1.0.100\"\n",
+                "+serde = \"1.0.210\"\n",
+                " toml = \"0.7\"\n",
+                " \n",
+                " [dev-dependencies]\n",
+                "@@ -12,4 +12,4 @@\n",
+                " d = \"1\"\n",
+                " e = \"1\"\n",
+                " f = \"1\"\n",
+                "-g = \"1\"\n",
+                "+g = \"2\"\n",
+            )
+        );
+        assert_eq!(unified_diff("Cargo.toml", before, before), "");
+    }
+
+    #[test]
+    fn test_unified_diff_without_trailing_newline() {
+        assert_eq!(
+            unified_diff("Cargo.toml", "a = \"1\"", "a = \"2\""),
+            "diff --git a/Cargo.toml b/Cargo.toml
+--- a/Cargo.toml
++++ b/Cargo.toml
+@@ -1 +1 @@
+-a = \"1\"
+\\ No newline at end of file
++a = \"2\"
+\\ No newline at end of file
+"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_inserted_lines() {
+        assert_eq!(
+            unified_diff("Cargo.toml", "", "a = \"1\"\n"),
+            "diff --git a/Cargo.toml b/Cargo.toml
+--- a/Cargo.toml
++++ b/Cargo.toml
+@@ -0,0 +1 @@
++a = \"1\"
+"
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use std::fmt;
+
+/// Failure while scanning the dependencies or applying the updates, to tell apart what went wrong
+#[derive(Debug)]
+pub enum Error {
+    /// A Cargo.toml file, or the patch file, could not be read or written
+    Io(std::io::Error),
+    /// A Cargo.toml file could not be read or parsed, when scanning or once written
+    Manifest(String),
+    /// A written Cargo.toml file does not require the new version of a dependency
+    NotUpdated(String),
+    /// A git command could not be run or failed
+    Git(String),
+    /// `cargo check` could not be run
+    Check(std::io::Error),
+    /// `cargo update` could not be run or failed, with `lock_only`
+    Lock(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Manifest(message)
+            | Self::NotUpdated(message)
+            | Self::Git(message)
+            | Self::Lock(message) => {
+                write!(f, "{message}")
+            }
+            Self::Check(e) => write!(f, "unable to run `cargo check`: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) | Self::Check(e) => Some(e),
+            Self::Manifest(_) | Self::NotUpdated(_) | Self::Git(_) | Self::Lock(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            Error::Git("failed to commit the updated files with `git commit`".to_string())
+                .to_string(),
+            "failed to commit the updated files with `git commit`"
+        );
+        assert_eq!(
+            Error::Check(std::io::Error::from(std::io::ErrorKind::NotFound)).to_string(),
+            "unable to run `cargo check`: entity not found"
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use crossterm::{
+    cursor::{MoveTo, MoveToNextLine},
+    queue,
+    style::Print,
+    terminal::{Clear, ClearType, DisableLineWrap, EnableLineWrap},
+};
+use std::{fmt::Display, io::Write};
+
+#[derive(Clone, PartialEq, Eq)]
+struct Line {
+    text: String,
+    /// Whether the line wraps over the next rows when wider than the terminal, instead of being
+    /// cut at its width
+    wrap: bool,
+}
+
+/// Lines of the screen, drawn by only rewriting the rows that changed since the previous frame,
+/// a full redraw on every keypress flickering on slow terminals and over SSH
+pub struct Frame {
+    lines: Vec<Line>,
+    width: usize,
+    height: usize,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            lines: vec![],
+            width,
+            height,
+        }
+    }
+
+    /// Adds a line, cut at the width of the terminal
+    pub fn line(&mut self, text: impl Display) {
+        self.lines.push(Line {
+            text: text.to_string(),
+            wrap: false,
+        });
+    }
+
+    /// Adds a line, wrapped over as many rows as needed
+    pub fn wrapped_line(&mut self, text: impl Display) {
+        self.lines.push(Line {
+            text: text.to_string(),
+            wrap: true,
+        });
+    }
+
+    pub fn blank(&mut self) {
+        self.line("");
+    }
+
+    /// Row on which each line starts, followed by the total number of rows
+    fn start_rows(&self) -> Vec<usize> {
+        let mut rows = Vec::with_capacity(self.lines.len() + 1);
+        let mut row = 0;
+        for line in &self.lines {
+            rows.push(row);
+            row += if line.wrap {
+                visible_width(&line.text).div_ceil(self.width.max(1)).max(1)
+            } else {
+                1
+            };
+        }
+        rows.push(row);
+        rows
+    }
+
+    /// Draws the frame over the previous one, only rewriting the lines that changed. Everything
+    /// is redrawn when there is no previous frame, the terminal was resized, or either frame
+    /// doesn't fit in it, the rows being out of reach once the terminal scrolled
+    pub fn draw(&self, out: &mut impl Write, previous: Option<&Frame>) -> std::io::Result<()> {
+        let rows = self.start_rows();
+        let total_rows = rows[self.lines.len()];
+
+        let Some(previous) = previous.filter(|previous| {
+            (previous.width, previous.height) == (self.width, self.height)
+                && total_rows <= self.height
+                && previous.start_rows()[previous.lines.len()] <= previous.height
+        }) else {
+            queue!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+            for (i, line) in self.lines.iter().enumerate() {
+                if i > 0 {
+                    queue!(out, MoveToNextLine(1))?;
+                }
+                print_line(out, line)?;
+            }
+            return Ok(());
+        };
+        let previous_rows = previous.start_rows();
+
+        // Lines keeping their place and a single row are rewritten in place, everything is
+        // rewritten from the first one that doesn't as the rows after it may have moved
+        let mut first_moved = self.lines.len();
+        for (i, line) in self.lines.iter().enumerate() {
+            let previous_line = previous.lines.get(i);
+            let same_row = previous_rows.get(i) == Some(&rows[i]) && i < previous.lines.len();
+            if same_row && previous_line == Some(line) {
+                continue;
+            }
+
+            if same_row && !line.wrap && previous_line.is_some_and(|l| !l.wrap) {
+                queue!(out, MoveTo(0, rows[i] as u16))?;
+                print_line(out, line)?;
+                queue!(out, Clear(ClearType::UntilNewLine))?;
+            } else {
+                first_moved = i;
+                break;
+            }
+        }
+
+        let previous_total_rows = previous_rows[previous.lines.len()];
+        if first_moved < self.lines.len() || total_rows < previous_total_rows {
+            queue!(
+                out,
+                MoveTo(0, rows[first_moved] as u16),
+                Clear(ClearType::FromCursorDown)
+            )?;
+            for (line, row) in self.lines[first_moved..].iter().zip(&rows[first_moved..]) {
+                queue!(out, MoveTo(0, *row as u16))?;
+                print_line(out, line)?;
+            }
+        }
+
+        // What is printed once the list is left, e.g. by `apply`, goes after the last row
+        queue!(out, MoveTo(0, total_rows.saturating_sub(1) as u16))?;
+
+        Ok(())
+    }
+}
+
+fn print_line(out: &mut impl Write, line: &Line) -> std::io::Result<()> {
+    if line.wrap {
+        queue!(out, Print(&line.text))
+    } else {
+        queue!(out, DisableLineWrap, Print(&line.text), EnableLineWrap)
+    }
+}
+
+/// Number of columns taken by the text, leaving out the escape sequences of its styles and links
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            width += 1;
+            continue;
+        }
+
+        match chars.next() {
+            // Control sequence, ended by a byte in the `@` to `~` range
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // Operating system command, ended by BEL or ESC \
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::style::Stylize;
+
+    fn draw(frame: &Frame, previous: Option<&Frame>) -> String {
+        let mut out = vec![];
+        frame.draw(&mut out, previous).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_visible_width() {
+        assert_eq!(visible_width("serde"), 5);
+        assert_eq!(visible_width(&format!("{}", "serde".cyan().bold())), 5);
+        assert_eq!(
+            visible_width("\x1b]8;;file:///Cargo.toml\x1b\\Cargo.toml\x1b]8;;\x1b\\"),
+            10
+        );
+        assert_eq!(visible_width("● serde"), 7);
+    }
+
+    #[test]
+    fn test_start_rows() {
+        let mut frame = Frame::new(10, 24);
+        frame.wrapped_line("a".repeat(25));
+        frame.blank();
+        frame.line("b".repeat(25));
+        frame.wrapped_line("c".repeat(10));
+        frame.wrapped_line("");
+        assert_eq!(frame.start_rows(), [0, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_draw_changed_lines_only() {
+        let frame = |cursor: usize| {
+            let mut frame = Frame::new(80, 24);
+            frame.wrapped_line("3 out of the 3 direct dependencies are outdated.");
+            for (i, name) in ["foo", "bar", "baz"].iter().enumerate() {
+                frame.line(if i == cursor {
+                    format!("> {name}")
+                } else {
+                    format!("  {name}")
+                });
+            }
+            frame
+        };
+
+        let first = draw(&frame(0), None);
+        assert!(first.starts_with("\x1b[2J"));
+        assert!(first.contains("> foo") && first.contains("  bar") && first.contains("  baz"));
+
+        let second = draw(&frame(1), Some(&frame(0)));
+        assert!(!second.contains("\x1b[2J"));
+        assert!(!second.contains("outdated") && !second.contains("baz"));
+        assert!(second.contains("\x1b[2;1H\x1b[?7l  foo"));
+        assert!(second.contains("\x1b[3;1H\x1b[?7l> bar"));
+
+        assert_eq!(draw(&frame(1), Some(&frame(1))), "\x1b[4;1H");
+    }
+
+    #[test]
+    fn test_draw_moved_lines() {
+        let mut previous = Frame::new(10, 24);
+        previous.wrapped_line("a".repeat(5));
+        previous.line("b");
+        previous.line("c");
+
+        let mut frame = Frame::new(10, 24);
+        frame.wrapped_line("a".repeat(15));
+        frame.line("b");
+
+        // The wrapped line takes one more row, the ones after it are rewritten
+        let output = draw(&frame, Some(&previous));
+        assert!(output.starts_with("\x1b[1;1H\x1b[J"));
+        assert!(output.contains("\x1b[3;1H\x1b[?7lb"));
+        assert!(!output.contains('c'));
+
+        // Lines removed at the end are cleared
+        let mut shorter = Frame::new(10, 24);
+        shorter.wrapped_line("a".repeat(15));
+        assert_eq!(draw(&shorter, Some(&frame)), "\x1b[3;1H\x1b[J\x1b[2;1H");
+    }
+
+    #[test]
+    fn test_draw_everything_when_resized() {
+        let mut previous = Frame::new(10, 24);
+        previous.line("a");
+        let mut frame = Frame::new(20, 24);
+        frame.line("a");
+
+        assert!(draw(&frame, Some(&previous)).starts_with("\x1b[2J"));
+    }
+}
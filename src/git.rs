@@ -0,0 +1,100 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::Error;
+
+fn git(dir: &Path) -> Command {
+    let mut command = Command::new("git");
+    command.current_dir(dir);
+    command
+}
+
+/// Runs the git command, capturing its output, and fails with the given message followed by what
+/// git printed to stderr when it doesn't succeed
+fn run(command: &mut Command, failure: &str) -> Result<(), Error> {
+    let output = command
+        .output()
+        .map_err(|e| Error::Git(format!("unable to run git: {e}")))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Git(if stderr.trim().is_empty() {
+            failure.to_string()
+        } else {
+            format!("{failure}: {}", stderr.trim())
+        }));
+    }
+
+    Ok(())
+}
+
+/// Whether the directory is inside a git working tree, `false` if git isn't installed either
+fn is_inside_work_tree(dir: &Path) -> bool {
+    git(dir)
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Stages the given files and commits them alone, leaving any other staged change untouched.
+/// Returns `false` without doing anything when the directory is not inside a git repository
+pub fn commit(dir: &Path, files: &[PathBuf], message: &str) -> Result<bool, Error> {
+    if !is_inside_work_tree(dir) {
+        return Ok(false);
+    }
+
+    // The paths are relative to the current directory rather than to `dir`
+    let files = files
+        .iter()
+        .map(std::fs::canonicalize)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    run(
+        git(dir).arg("add").arg("--").args(&files),
+        "failed to stage the updated files with `git add`",
+    )?;
+
+    run(
+        git(dir)
+            .args(["commit", "--quiet", "--message", message, "--"])
+            .args(&files),
+        "failed to commit the updated files with `git commit`",
+    )?;
+
+    Ok(true)
+}
+
+/// Creates a new branch and switches to it, keeping any uncommitted change. Returns `false`
+/// without doing anything when the directory is not inside a git repository
+pub fn create_branch(dir: &Path, name: &str) -> Result<bool, Error> {
+    if !is_inside_work_tree(dir) {
+        return Ok(false);
+    }
+
+    run(
+        git(dir).args(["checkout", "--quiet", "-b", name]),
+        &format!("failed to create the branch `{name}` with `git checkout`"),
+    )?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_outside_git_repository() {
+        let dir = std::env::temp_dir().join(format!("git-commit-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        if !is_inside_work_tree(&dir) {
+            assert!(!commit(&dir, &[dir.join("Cargo.toml")], "message").unwrap());
+            assert!(!create_branch(&dir, "branch").unwrap());
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
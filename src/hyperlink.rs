@@ -0,0 +1,104 @@
+use std::path::Path;
+
+/// Whether the terminal is known to support OSC 8 hyperlinks. Unknown terminals, and terminal
+/// multiplexers which may not pass them through, get plain text, unless `FORCE_HYPERLINK` is set
+pub fn is_supported() -> bool {
+    is_supported_by(|name| std::env::var(name).ok())
+}
+
+fn is_supported_by(var: impl Fn(&str) -> Option<String>) -> bool {
+    if let Some(force) = var("FORCE_HYPERLINK").filter(|force| !force.is_empty()) {
+        return force != "0";
+    }
+    if var("TERM").as_deref() == Some("dumb") || var("TMUX").is_some() || var("STY").is_some() {
+        return false;
+    }
+
+    var("WT_SESSION").is_some()
+        || var("KITTY_WINDOW_ID").is_some()
+        || var("DOMTERM").is_some()
+        || var("TERM_PROGRAM").is_some_and(|program| {
+            ["iTerm.app", "WezTerm", "vscode", "ghostty"].contains(&&*program)
+        })
+        || var("VTE_VERSION")
+            .and_then(|version| version.parse::<u32>().ok())
+            .is_some_and(|version| version >= 5000)
+}
+
+/// The text as a hyperlink to the given URL, with the OSC 8 escape sequence
+pub fn link(text: &str, url: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+/// `file://` URL of the given absolute path, percent-encoding the characters that would end it
+pub fn file_url(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut url = String::from("file://");
+    if !path.starts_with('/') {
+        url.push('/');
+    }
+
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/:".contains(&byte) {
+            url.push(byte as char);
+        } else {
+            url.push_str(&format!("%{byte:02X}"));
+        }
+    }
+
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_supported_by() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+
+        assert!(!is_supported_by(env(&[])));
+        assert!(!is_supported_by(env(&[("TERM_PROGRAM", "Apple_Terminal")])));
+        assert!(is_supported_by(env(&[("TERM_PROGRAM", "WezTerm")])));
+        assert!(is_supported_by(env(&[("VTE_VERSION", "7600")])));
+        assert!(!is_supported_by(env(&[("VTE_VERSION", "4600")])));
+        assert!(!is_supported_by(env(&[
+            ("TERM_PROGRAM", "iTerm.app"),
+            ("TMUX", "/tmp/tmux-1000/default,1234,0"),
+        ])));
+        assert!(is_supported_by(env(&[
+            ("TMUX", "1"),
+            ("FORCE_HYPERLINK", "1")
+        ])));
+        assert!(!is_supported_by(env(&[
+            ("WT_SESSION", "1"),
+            ("FORCE_HYPERLINK", "0")
+        ])));
+    }
+
+    #[test]
+    fn test_link() {
+        assert_eq!(
+            link("Cargo.toml", "file:///home/user/Cargo.toml"),
+            "\x1b]8;;file:///home/user/Cargo.toml\x1b\\Cargo.toml\x1b]8;;\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_file_url() {
+        assert_eq!(
+            file_url(Path::new("/home/user/my project/Cargo.toml")),
+            "file:///home/user/my%20project/Cargo.toml"
+        );
+        assert_eq!(
+            file_url(Path::new(r"C:\Users\user\Cargo.toml")),
+            "file:///C:/Users/user/Cargo.toml"
+        );
+    }
+}
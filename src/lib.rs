@@ -0,0 +1,338 @@
+//! Library API of `cargo interactive-update`, to find and update outdated direct dependencies
+//! without going through the interactive terminal UI.
+
+use std::{
+    path::PathBuf,
+    process::Output,
+    time::{Duration, Instant},
+};
+
+mod api;
+mod cargo;
+mod config;
+mod dependency;
+mod diff;
+mod error;
+mod git;
+
+pub use config::Config;
+pub use dependency::{
+    DateField, Dependencies, Dependency, DependencyKind, IgnoredVersion, SortOrder, UpdateType,
+    VersionOperator,
+};
+pub use error::Error;
+
+/// Options used to find outdated dependencies, the default ones scanning the current directory
+#[derive(Default)]
+pub struct ScanOptions {
+    /// Directory containing the Cargo.toml file to scan
+    pub manifest_dir: PathBuf,
+    /// Suggest the latest versions even if they require a newer Rust version than the
+    /// `rust-version` of the package
+    pub ignore_rust_version: bool,
+    /// Look up the known security advisories affecting the current version of the outdated
+    /// dependencies, sending their names and versions to OSV (api.osv.dev)
+    pub check_advisories: bool,
+    /// Names of the dependencies to never report as outdated, replacing the ones from the config
+    pub exclude: Vec<String>,
+    /// Versions of the dependencies to never suggest, falling back to the newest other version
+    /// released since the current one, replacing the ones from the config
+    pub ignore: Vec<IgnoredVersion>,
+    /// Kinds of dependencies to leave out, before checking them against crates.io
+    pub exclude_kinds: Vec<DependencyKind>,
+    /// Time budget for the whole scan, after which the dependencies not checked yet are skipped
+    pub max_time: Option<Duration>,
+    /// Also check the crates.io packages of the Cargo.lock file that are not direct dependencies
+    pub include_transitive: bool,
+    /// User-Agent sent with every request, instead of the name and repository of this tool
+    pub user_agent: Option<String>,
+    /// Timestamp of crates.io used as the date of the current and latest versions
+    pub date_field: DateField,
+    /// Suggest the nearest version that wasn't yanked, rather than the latest one, for the
+    /// dependencies whose current version was yanked
+    pub include_yanked_current: bool,
+    /// Only check the dependencies pinned to an exact version with `=`, which keep the operator
+    /// when updated
+    pub pinned_only: bool,
+    /// Levels of workspace members to gather the dependencies of, `Some(0)` only gathering the
+    /// ones of the root package
+    pub depth: Option<usize>,
+}
+
+/// Options used to write the updated versions, the default ones updating the Cargo.toml files of
+/// the current directory in place, without running `cargo check`
+#[derive(Default)]
+pub struct ApplyOptions {
+    /// Directory containing the Cargo.toml file to update
+    pub manifest_dir: PathBuf,
+    /// Pin dependencies to exact versions, with an `=` prefix
+    pub pin: bool,
+    /// Run `cargo check` after updating
+    pub check: bool,
+    /// Write and report each kind of dependencies separately
+    pub split_by_kind: bool,
+    /// Commit the updated Cargo.toml files, once per kind of dependencies with `split_by_kind`
+    pub commit: bool,
+    /// Name of a new git branch to create and switch to before updating
+    pub branch: Option<String>,
+    /// Write the changes as a unified diff to this file instead of updating the Cargo.toml files
+    pub output_patch: Option<PathBuf>,
+    /// Write the updated Cargo.toml files of the package and its workspace members under this
+    /// directory, keeping their layout, instead of updating them in place
+    pub output_dir: Option<PathBuf>,
+    /// Only update Cargo.lock with `cargo update --precise`, for the updates allowed by the
+    /// current requirements, leaving the Cargo.toml files untouched and the other updates out
+    pub lock_only: bool,
+    /// Run `cargo check` with `--locked`, failing if Cargo.lock needs to be updated
+    pub locked: bool,
+    /// Run `cargo check` with `--frozen`, failing if Cargo.lock needs to be updated or the network
+    /// needs to be reached
+    pub frozen: bool,
+}
+
+/// Total number of direct dependencies, the ones that are outdated and the ones already up to
+/// date, along with the config of the scanned package
+pub struct ScanResult {
+    pub total_deps: usize,
+    pub outdated_deps: Dependencies,
+    pub up_to_date_deps: Vec<Dependency>,
+    /// Dependencies requiring a version newer than the latest one on crates.io
+    pub ahead_deps: Vec<Dependency>,
+    /// Number of dependencies not checked because the scan exceeded its `max_time`
+    pub unchecked_deps: usize,
+    /// Names of the dependencies that could not be checked, with the reason
+    pub failed_deps: Vec<(String, String)>,
+    /// Outdated packages of the Cargo.lock file that are not direct dependencies, with
+    /// `include_transitive`
+    pub transitive_deps: Vec<Dependency>,
+    /// Declared dependencies that were not checked, and why
+    pub skipped_deps: SkippedDependencies,
+    /// Source replacing crates.io in the Cargo config, which cargo resolves the dependencies
+    /// from, while their latest versions are still checked against crates.io
+    pub crates_io_replacement: Option<String>,
+    pub config: Config,
+}
+
+/// Names of the declared dependencies left out of the scan, for each reason
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SkippedDependencies {
+    /// Excluded by name or by kind
+    pub excluded: Vec<String>,
+    /// Declared without a version requirement, e.g. path or git dependencies
+    pub without_version: Vec<String>,
+    /// With a requirement that has no single lowest version, e.g. `>=1, <2`
+    pub unsupported_requirement: Vec<String>,
+    /// From another registry than crates.io, e.g. with `registry = "my-corp"`
+    pub other_registry: Vec<String>,
+    /// Entries of the `[patch.crates-io]` table of the root, from a git repository or a path,
+    /// whose version is not compared with crates.io. The dependencies they override are still
+    /// checked, and marked as `patched`
+    pub patched: Vec<String>,
+}
+
+/// What [`apply`] did, for the caller to report it
+#[derive(Default)]
+pub struct ApplyReport {
+    /// New git branch switched to, with `branch`, `None` outside of a git repository
+    pub branch: Option<String>,
+    /// Files written: the Cargo.toml files, the patch file, or Cargo.lock with `lock_only`
+    pub written_files: Vec<PathBuf>,
+    /// Updates written, in the order of the kinds of dependencies they were written in
+    pub updated: Vec<AppliedUpdate>,
+    /// Updates needing a new requirement in Cargo.toml, left out with `lock_only`
+    pub skipped: Vec<AppliedUpdate>,
+    /// Messages of the commits created, with `commit`, none outside of a git repository
+    pub commits: Vec<String>,
+    /// Output of `cargo check`, with `check`
+    pub check: Option<Output>,
+}
+
+/// Update of a dependency, from its requirement to the new one
+pub struct AppliedUpdate {
+    pub name: String,
+    pub kind: DependencyKind,
+    pub requirement: String,
+    pub new_requirement: String,
+    /// Cargo.toml file declaring the dependency
+    pub manifest_path: PathBuf,
+}
+
+impl ScanResult {
+    fn new(
+        total_deps: usize,
+        retrieved: cargo::RetrievedDependencies,
+        transitive: cargo::RetrievedDependencies,
+        skipped_deps: SkippedDependencies,
+        crates_io_replacement: Option<String>,
+        config: Config,
+    ) -> Self {
+        Self {
+            total_deps,
+            outdated_deps: retrieved.outdated,
+            up_to_date_deps: retrieved.up_to_date,
+            ahead_deps: retrieved.ahead,
+            unchecked_deps: retrieved.unchecked + transitive.unchecked,
+            failed_deps: [retrieved.failed, transitive.failed].concat(),
+            transitive_deps: transitive.outdated.dependencies,
+            skipped_deps,
+            crates_io_replacement,
+            config,
+        }
+    }
+}
+
+/// Gathers the direct dependencies of the package and its workspace members, without the
+/// excluded ones and kinds, which are reported along with the other ones that won't be checked
+fn gather_dependencies(
+    options: &ScanOptions,
+) -> Result<(cargo::CargoDependencies, SkippedDependencies, Config, bool), Error> {
+    let mut dependencies =
+        cargo::CargoDependencies::gather_dependencies(&options.manifest_dir, options.depth)?;
+    let config = Config::from_cargo_toml(&dependencies.cargo_toml);
+    let declared = dependencies.names();
+
+    dependencies.exclude(if options.exclude.is_empty() {
+        &config.exclude
+    } else {
+        &options.exclude
+    });
+    dependencies.exclude_kinds(&options.exclude_kinds);
+
+    let remaining = dependencies.names();
+    let skipped = SkippedDependencies {
+        excluded: declared
+            .into_iter()
+            .filter(|name| remaining.binary_search(name).is_err())
+            .collect(),
+        without_version: dependencies.names_without_version(),
+        unsupported_requirement: dependencies.names_with_unsupported_requirement(),
+        other_registry: dependencies.names_from_other_registry(),
+        patched: dependencies.patched_names(),
+    };
+    if options.pinned_only {
+        dependencies.retain_pinned();
+    }
+
+    let ignore_rust_version = options.ignore_rust_version || config.ignore_rust_version;
+    Ok((dependencies, skipped, config, ignore_rust_version))
+}
+
+fn ignored_versions<'a>(options: &'a ScanOptions, config: &'a Config) -> &'a [IgnoredVersion] {
+    if options.ignore.is_empty() {
+        &config.ignore
+    } else {
+        &options.ignore
+    }
+}
+
+fn get_user_agent(options: &ScanOptions) -> String {
+    options
+        .user_agent
+        .clone()
+        .unwrap_or_else(api::get_default_user_agent)
+}
+
+/// Gathers the direct dependencies of the package and its workspace members, and retrieves the
+/// ones that are outdated from crates.io. Fails when a Cargo.toml file can't be read or parsed
+pub fn scan_outdated(options: &ScanOptions) -> Result<ScanResult, Error> {
+    scan(options, None)
+}
+
+/// Same as [`scan_outdated`], only checking the direct dependencies with the given names, e.g. to
+/// retry the ones that could not be checked. `include_transitive` is ignored
+pub fn rescan_outdated(options: &ScanOptions, names: &[String]) -> Result<ScanResult, Error> {
+    scan(options, Some(names))
+}
+
+fn scan(options: &ScanOptions, names: Option<&[String]>) -> Result<ScanResult, Error> {
+    let (mut dependencies, skipped, config, ignore_rust_version) = gather_dependencies(options)?;
+    if let Some(names) = names {
+        dependencies.retain_names(names);
+    }
+
+    let user_agent = get_user_agent(options);
+    // Shared by the direct and transitive dependencies, the direct ones being checked first as
+    // they are the only ones that can be updated
+    let deadline = options.max_time.map(|max_time| Instant::now() + max_time);
+
+    let total_deps = dependencies.len();
+    let transitive_dependencies =
+        (options.include_transitive && names.is_none()).then(|| dependencies.clone());
+    let mut retrieved = dependencies.retrieve_outdated_dependencies(
+        None,
+        ignore_rust_version,
+        options.check_advisories,
+        options.date_field,
+        deadline,
+        &user_agent,
+    );
+    retrieved
+        .outdated
+        .skip_ignored_versions(ignored_versions(options, &config));
+    if options.include_yanked_current {
+        retrieved.outdated.target_nearest_non_yanked_versions();
+    }
+
+    let transitive = transitive_dependencies
+        .map(|dependencies| {
+            dependencies.retrieve_outdated_transitive_dependencies(
+                &options.manifest_dir,
+                ignore_rust_version,
+                options.check_advisories,
+                options.date_field,
+                deadline,
+                &user_agent,
+            )
+        })
+        .unwrap_or_default();
+
+    Ok(ScanResult::new(
+        total_deps,
+        retrieved,
+        transitive,
+        skipped,
+        cargo::get_crates_io_replacement(&options.manifest_dir),
+        config,
+    ))
+}
+
+/// Same as [`scan_outdated`], fetching from crates.io concurrently with non-blocking requests
+/// instead of a pool of threads, to be called from within a Tokio runtime. `max_time` is
+/// ignored, the caller being free to wrap the future in its own timeout, and so is
+/// `include_transitive`
+#[cfg(feature = "async")]
+pub async fn scan_outdated_async(options: &ScanOptions) -> Result<ScanResult, Error> {
+    let (dependencies, skipped, config, ignore_rust_version) = gather_dependencies(options)?;
+
+    let total_deps = dependencies.len();
+    let mut retrieved = dependencies
+        .retrieve_outdated_dependencies_async(
+            None,
+            ignore_rust_version,
+            options.check_advisories,
+            options.date_field,
+            &get_user_agent(options),
+        )
+        .await;
+    retrieved
+        .outdated
+        .skip_ignored_versions(ignored_versions(options, &config));
+    if options.include_yanked_current {
+        retrieved.outdated.target_nearest_non_yanked_versions();
+    }
+
+    Ok(ScanResult::new(
+        total_deps,
+        retrieved,
+        Default::default(),
+        skipped,
+        cargo::get_crates_io_replacement(&options.manifest_dir),
+        config,
+    ))
+}
+
+/// Writes the latest versions of the given dependencies to their Cargo.toml files, returning what
+/// was done without printing anything. The output of `cargo check` is captured in the report
+pub fn apply(mut selected: Dependencies, options: &ApplyOptions) -> Result<ApplyReport, Error> {
+    selected.apply_versions(options)
+}
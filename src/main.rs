@@ -1,33 +1,227 @@
 use clap::Parser;
+use crossterm::style::Stylize;
+use std::process::ExitCode;
 
-mod api;
 mod args;
-mod cargo;
 mod cli;
-mod dependency;
+mod clipboard;
+mod frame;
+mod hyperlink;
+mod selection;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// Exit code used when the user quits the interactive selection without applying any update
+const EXIT_CODE_QUIT: u8 = 2;
+/// Exit code used in check mode when at least one dependency is outdated
+const EXIT_CODE_OUTDATED: u8 = 3;
+/// Exit code used in strict mode when at least one dependency could not be checked
+const EXIT_CODE_UNCHECKED: u8 = 4;
+
+fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
     let args::CargoCli::InteractiveUpdate(args) = args::CargoCli::parse();
 
-    let dependencies = cargo::CargoDependencies::gather_dependencies(".");
-    let total_deps = dependencies.len();
-    let outdated_deps = dependencies.retrieve_outdated_dependencies(None);
+    args.validate()?;
+
+    let cargo_interactive_update::ScanResult {
+        total_deps,
+        mut outdated_deps,
+        up_to_date_deps,
+        ahead_deps,
+        unchecked_deps,
+        failed_deps,
+        transitive_deps,
+        skipped_deps,
+        crates_io_replacement,
+        config,
+    } = cargo_interactive_update::scan_outdated(&args.scan_options())?;
+    let up_to_date_deps = if args.show_current {
+        up_to_date_deps
+    } else {
+        vec![]
+    };
     let total_outdated_deps = outdated_deps.len();
+    outdated_deps.sort_by_order(args.sort);
+
+    if args.list {
+        cli::print_list(&outdated_deps);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(args::OutputFormat::Markdown) = args.format {
+        cli::print_markdown(&outdated_deps);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(replacement) = &crates_io_replacement {
+        println!(
+            "{}",
+            format!("crates.io is replaced with the \"{replacement}\" source in the Cargo config, the latest versions are checked against crates.io and may not be available there.").yellow()
+        );
+    }
+
+    if let Some(skipped) = cli::format_skipped(&skipped_deps) {
+        println!("{skipped}");
+    }
+
+    if unchecked_deps > 0 {
+        println!(
+            "{}",
+            format!("{unchecked_deps} dependencies were not checked due to timeout.").yellow()
+        );
+    }
+
+    if !failed_deps.is_empty() {
+        println!(
+            "{}",
+            format!("{} dependencies could not be checked:", failed_deps.len()).yellow()
+        );
+        for (name, reason) in &failed_deps {
+            println!("  {}: {reason}", name.as_str().bold());
+        }
+    }
+
+    if !ahead_deps.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "{} dependencies are ahead of the registry, their latest version may have been yanked:",
+                ahead_deps.len()
+            )
+            .yellow()
+        );
+        for dependency in &ahead_deps {
+            println!(
+                "  {}: {} (latest {})",
+                dependency.name.as_str().bold(),
+                dependency.requirement,
+                dependency.latest_version
+            );
+        }
+    }
+
+    if args.strict && (unchecked_deps > 0 || !failed_deps.is_empty()) {
+        return Ok(ExitCode::from(EXIT_CODE_UNCHECKED));
+    }
 
     if total_outdated_deps == 0 {
         println!("All {total_deps} direct dependencies are up to date!");
-        return Ok(());
+        cli::print_up_to_date_dependencies(&up_to_date_deps);
+        cli::print_transitive_dependencies(&transitive_deps);
+        return Ok(ExitCode::SUCCESS);
     }
 
-    println!("{total_outdated_deps} out of the {total_deps} direct dependencies are outdated.");
+    println!(
+        "{total_outdated_deps} out of the {total_deps} direct dependencies are outdated {}.",
+        cli::format_kind_counts(&outdated_deps)
+    );
 
-    let mut state = cli::State::new(outdated_deps, total_deps, args.all);
+    if args.check {
+        cli::print_dependencies(&outdated_deps);
+        cli::print_up_to_date_dependencies(&up_to_date_deps);
+        cli::print_transitive_dependencies(&transitive_deps);
+        return Ok(ExitCode::from(EXIT_CODE_OUTDATED));
+    }
+
+    let previously_selected =
+        if args.fresh || args.yes || args.security_only || args.preselect.is_some() {
+            Default::default()
+        } else {
+            selection::load_selection(args.manifest_dir())
+        };
+    let select_all = (args.all || config.all) && !args.security_only && args.preselect.is_none();
+    let default_selected = |dependency: &cargo_interactive_update::Dependency| {
+        select_all
+            || (args.include_yanked_current && dependency.current_version_yanked)
+            || args
+                .preselect
+                .is_some_and(|update_type| dependency.update_type() == Some(update_type))
+    };
+    let mut state = cli::State::new(
+        outdated_deps,
+        total_deps,
+        default_selected,
+        &previously_selected,
+        up_to_date_deps,
+        unchecked_deps,
+        transitive_deps,
+    );
+    state.set_sort_order(args.sort);
+    state.set_failed_deps(failed_deps);
+    state.set_manifest_dir(args.manifest_dir());
 
     if args.yes {
-        state.selected_dependencies().apply_versions(args)?;
-        return Ok(());
+        let mut dependencies = state.selected_dependencies();
+
+        if args.security_only {
+            dependencies = dependencies.with_advisories_only();
+        }
+
+        let major_updates = dependencies
+            .iter()
+            .filter(|d| d.is_major_update())
+            .collect::<Vec<_>>();
+
+        if !major_updates.is_empty() {
+            println!(
+                "\n{}",
+                format!("{} major (breaking) updates:", major_updates.len())
+                    .yellow()
+                    .bold()
+            );
+            for dependency in major_updates {
+                println!(
+                    "  {}: {} -> {}",
+                    dependency.name.as_str().bold(),
+                    dependency.requirement,
+                    dependency.latest_version
+                );
+            }
+
+            if !args.allow_major {
+                println!(
+                    "{}",
+                    "Skipping them, use --allow-major to apply them as well.".yellow()
+                );
+                dependencies = dependencies.without_major_updates();
+            }
+        }
+
+        let rust_version_warnings = cli::format_rust_version_warnings(dependencies.iter());
+
+        // A stray `--yes` shouldn't silently apply a large or breaking batch of updates, nor
+        // major updates requiring a newer Rust, which always count as breaking
+        if !args.force && cli::exceeds_changes_limit(&dependencies, args.max_changes) {
+            println!(
+                "\n{}",
+                format!(
+                    "About to apply {} updates, including {} major ones:",
+                    dependencies.len(),
+                    dependencies.iter().filter(|d| d.is_major_update()).count()
+                )
+                .yellow()
+                .bold()
+            );
+            cli::print_dependencies(&dependencies);
+            if let Some(warnings) = &rust_version_warnings {
+                println!("\n{warnings}");
+            }
+            println!();
+
+            if !cli::confirm("Apply them?")? {
+                println!("Nothing was updated, use --force to apply them without confirmation.");
+                return Ok(ExitCode::from(EXIT_CODE_QUIT));
+            }
+        } else if let Some(warnings) = &rust_version_warnings {
+            println!("\n{warnings}");
+        }
+
+        let options = args.apply_options(&config);
+        let report = cargo_interactive_update::apply(dependencies, &options)?;
+        cli::print_apply_report(&report, &options);
+        return Ok(ExitCode::SUCCESS);
     }
 
+    // The terminal is restored once the state is dropped, e.g. when it is consumed by
+    // `selected_dependencies` before applying the updates
     state.start()?;
 
     loop {
@@ -36,14 +230,88 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         match state.handle_keyboard_event()? {
             cli::Event::HandleKeyboard => {}
             cli::Event::UpdateDependencies => {
-                state.selected_dependencies().apply_versions(args)?;
-                break;
+                selection::clear_selection(args.manifest_dir());
+                let dependencies = state.selected_dependencies();
+                let options = args.apply_options(&config);
+                let report = cargo_interactive_update::apply(dependencies, &options)?;
+                cli::print_apply_report(&report, &options);
+                if !args.keep_going {
+                    break;
+                }
+
+                // Updating a dependency, a major one especially, may make newer versions of the
+                // others compatible
+                println!("\nChecking the dependencies again...");
+                let result = cargo_interactive_update::scan_outdated(&args.scan_options())?;
+                if result.outdated_deps.is_empty() {
+                    println!(
+                        "All {} direct dependencies are up to date!",
+                        result.total_deps
+                    );
+                    break;
+                }
+
+                let mut outdated_deps = result.outdated_deps;
+                outdated_deps.sort_by_order(args.sort);
+                state = cli::State::new(
+                    outdated_deps,
+                    result.total_deps,
+                    default_selected,
+                    &Default::default(),
+                    if args.show_current {
+                        result.up_to_date_deps
+                    } else {
+                        vec![]
+                    },
+                    result.unchecked_deps,
+                    result.transitive_deps,
+                );
+                state.set_sort_order(args.sort);
+                state.set_failed_deps(result.failed_deps);
+                state.set_manifest_dir(args.manifest_dir());
+                state.start()?;
+            }
+            cli::Event::RefreshFailed => {
+                let names = state.failed_names();
+                if !names.is_empty() {
+                    let mut result =
+                        cargo_interactive_update::rescan_outdated(&args.scan_options(), &names)?;
+                    if !args.show_current {
+                        result.up_to_date_deps.clear();
+                    }
+                    state.refresh_failed(result);
+                }
+            }
+            cli::Event::RefreshAll => {
+                let mut result = cargo_interactive_update::scan_outdated(&args.scan_options())?;
+                if !args.show_current {
+                    result.up_to_date_deps.clear();
+                }
+                if result.outdated_deps.is_empty() {
+                    drop(state);
+                    selection::clear_selection(args.manifest_dir());
+                    println!(
+                        "\n\nAll {} direct dependencies are up to date!",
+                        result.total_deps
+                    );
+                    break;
+                }
+                state.refresh_all(result);
             }
             cli::Event::Exit => {
-                break;
+                // The saved selection is only a convenience, quitting shouldn't fail without it
+                let saved = selection::save_selection(args.manifest_dir(), &state.selected_names());
+                if let Err(e) = saved {
+                    drop(state);
+                    eprintln!(
+                        "{}",
+                        format!("\n\nUnable to save the selection for the next run: {e}").yellow()
+                    );
+                }
+                return Ok(ExitCode::from(EXIT_CODE_QUIT));
             }
         }
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
@@ -0,0 +1,67 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+/// Location of the file keeping the names of the dependencies selected during the last run
+fn get_selection_file_path(manifest_dir: &Path) -> PathBuf {
+    manifest_dir
+        .join("target")
+        .join("interactive-update")
+        .join("selection")
+}
+
+pub fn load_selection(manifest_dir: &Path) -> HashSet<String> {
+    std::fs::read_to_string(get_selection_file_path(manifest_dir))
+        .map(|content| {
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn save_selection(
+    manifest_dir: &Path,
+    selected_names: &[&str],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = get_selection_file_path(manifest_dir);
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(path, selected_names.join("\n"))?;
+    Ok(())
+}
+
+pub fn clear_selection(manifest_dir: &Path) {
+    let _ = std::fs::remove_file(get_selection_file_path(manifest_dir));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load_selection() {
+        let manifest_dir =
+            std::env::temp_dir().join(format!("interactive-update-{}", std::process::id()));
+
+        assert!(load_selection(&manifest_dir).is_empty());
+
+        save_selection(&manifest_dir, &["serde", "toml"]).unwrap();
+        assert_eq!(
+            load_selection(&manifest_dir),
+            HashSet::from(["serde".to_string(), "toml".to_string()])
+        );
+
+        clear_selection(&manifest_dir);
+        assert!(load_selection(&manifest_dir).is_empty());
+
+        std::fs::remove_dir_all(manifest_dir).unwrap();
+    }
+}